@@ -0,0 +1,290 @@
+use bevy_math::Vec2;
+use std::{collections::HashMap, error::Error, fs, path::Path};
+
+use crate::font::{Char, DistanceField, Font, VectorFont};
+
+/// Generates SDF font atlases from TrueType/OpenType fonts.
+/// The result is a regular `Font`, use `Font::save_fnt` to store it as BMFont files.
+pub struct FontBaker {
+    /// Em size in pixels
+    pub size: f32,
+    /// SDF spread on each side of the glyph edge, glyphs are padded with it
+    pub padding: f32,
+    pub page_width: usize,
+    pub page_height: usize,
+    pub chars: Vec<char>,
+}
+
+impl FontBaker {
+    pub fn new() -> FontBaker {
+        FontBaker {
+            size: 32.0,
+            padding: 8.0,
+            page_width: 512,
+            page_height: 512,
+            chars: (32u8..=126).map(char::from).collect(),
+        }
+    }
+
+    pub fn bake<P: AsRef<Path>>(&self, path: P) -> Result<Font, Box<dyn Error>> {
+        self.bake_data(&fs::read(path)?)
+    }
+
+    pub fn bake_data(&self, data: &[u8]) -> Result<Font, Box<dyn Error>> {
+        self.bake_vector_font(&VectorFont::from_data(data, &self.chars)?)
+    }
+
+    /// Bakes the glyphs of `chars` available in the vector font
+    pub fn bake_vector_font(&self, vector_font: &VectorFont) -> Result<Font, Box<dyn Error>> {
+        let scale = self.size / vector_font.units_per_em;
+        let ascender = vector_font.ascender;
+        let padding = self.padding.ceil() as usize;
+        let mut font = Font {
+            size: self.size,
+            line_height: vector_font.line_height * scale,
+            base: ascender * scale,
+            scale_width: self.page_width as f32,
+            scale_height: self.page_height as f32,
+            chars: HashMap::new(),
+            kernings: vector_font.kernings.iter().map(|(&pair, &amount)| (pair, amount * scale)).collect(),
+            fallback: '?' as u32,
+            distance_field: DistanceField::Sdf,
+            distance_range: self.padding * 2.0,
+            pages: vec![],
+        };
+
+        // Shelf packing: glyphs are placed left to right, a new row starts when
+        // the current one is full and a new page when the rows reach the bottom
+        let mut cursor = (0, 0);
+        let mut row_height = 0;
+
+        for &letter in &self.chars {
+            let glyph = match vector_font.glyphs.get(&(letter as u32)) {
+                Some(glyph) => glyph,
+                None => continue,
+            };
+            let mut char = Char {
+                code: glyph.code,
+                page: 0,
+                width: 0.0,
+                height: 0.0,
+                x: 0.0,
+                x_advance: glyph.x_advance * scale,
+                x_offset: 0.0,
+                y: 0.0,
+                y_offset: 0.0,
+            };
+
+            // Glyphs without outline (e.g. space) only advance the cursor
+            let (min, max) = match glyph.outline.get_bounds() {
+                Some(bounds) => bounds,
+                None => {
+                    font.chars.insert(char.code, char);
+                    continue;
+                }
+            };
+
+            // Atlas pixels are relative to the pen position at the top of the line (y axis pointing down),
+            // outlines are in font units (y axis pointing up, origin at the baseline)
+            let to_pixels = |point: Vec2| Vec2::new(point.x * scale, (ascender - point.y) * scale);
+            let to_font_units = |point: Vec2| Vec2::new(point.x / scale, ascender - point.y / scale);
+            let (min, max) = (to_pixels(Vec2::new(min.x, max.y)), to_pixels(Vec2::new(max.x, min.y)));
+
+            let origin = Vec2::new(min.x.floor(), min.y.floor()) - Vec2::new(padding as f32, padding as f32);
+            let width = (max.x.ceil() - min.x.floor()) as usize + padding * 2 + 1;
+            let height = (max.y.ceil() - min.y.floor()) as usize + padding * 2 + 1;
+
+            if width > self.page_width || height > self.page_height {
+                return Err(format!("glyph `{}` doesn't fit in a {}x{} page", letter, self.page_width, self.page_height).into());
+            }
+
+            if cursor.0 + width > self.page_width {
+                cursor = (0, cursor.1 + row_height);
+                row_height = 0;
+            }
+
+            if font.pages.is_empty() || cursor.1 + height > self.page_height {
+                font.pages.push(vec![0; self.page_width * self.page_height * 4]);
+                cursor = (0, 0);
+                row_height = 0;
+            }
+
+            let page_index = font.pages.len() - 1;
+            let page = &mut font.pages[page_index];
+
+            // Pixel (i, j) holds the distance at `origin + (i, j)`, matching `Font::get_distance` sampling
+            for j in 0..height {
+                for i in 0..width {
+                    let point = to_font_units(origin + Vec2::new(i as f32, j as f32));
+                    let distance = glyph.outline.get_distance(point) * scale;
+                    let value = (0.5 - distance / font.distance_range).clamp(0.0, 1.0);
+                    let index = ((cursor.1 + j) * self.page_width + cursor.0 + i) * 4;
+
+                    page[index..index + 4].copy_from_slice(&[0xFF, 0xFF, 0xFF, (value * 255.0).round() as u8]);
+                }
+            }
+
+            char.page = page_index;
+            char.x = cursor.0 as f32;
+            char.y = cursor.1 as f32;
+            char.width = width as f32;
+            char.height = height as f32;
+            char.x_offset = origin.x;
+            char.y_offset = origin.y;
+            font.chars.insert(char.code, char);
+
+            cursor.0 += width;
+            row_height = row_height.max(height);
+        }
+
+        if font.pages.is_empty() {
+            font.pages.push(vec![0; self.page_width * self.page_height * 4]);
+        }
+
+        Ok(font)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ttf_parser::OutlineBuilder;
+
+    use crate::font::VectorGlyph;
+    use crate::outline::Outline;
+    use crate::utils::get_temp_directory;
+
+    // Squares of growing size plus an `o` (ring made of quadratic curves), in font units
+    fn get_vector_font() -> VectorFont {
+        let mut glyphs = HashMap::new();
+
+        for (index, letter) in ('A'..='Z').enumerate() {
+            let size = 200.0 + index as f32 * 20.0;
+            let mut outline = Outline::new();
+
+            outline.move_to(50.0, 0.0);
+            outline.line_to(50.0 + size, 0.0);
+            outline.line_to(50.0 + size, size);
+            outline.line_to(50.0, size);
+            outline.close();
+
+            glyphs.insert(letter as u32, VectorGlyph { code: letter as u32, x_advance: size + 100.0, outline });
+        }
+
+        let mut outline = Outline::new();
+
+        for &(radius, direction) in &[(300.0, 1.0), (150.0, -1.0)] {
+            let (cx, cy) = (350.0, 350.0);
+
+            outline.move_to(cx + radius, cy);
+            outline.quad_to(cx + radius, cy + radius * direction, cx, cy + radius * direction);
+            outline.quad_to(cx - radius, cy + radius * direction, cx - radius, cy);
+            outline.quad_to(cx - radius, cy - radius * direction, cx, cy - radius * direction);
+            outline.quad_to(cx + radius, cy - radius * direction, cx + radius, cy);
+            outline.close();
+        }
+
+        glyphs.insert('o' as u32, VectorGlyph { code: 'o' as u32, x_advance: 700.0, outline });
+        glyphs.insert(' ' as u32, VectorGlyph { code: ' ' as u32, x_advance: 250.0, outline: Outline::new() });
+
+        VectorFont {
+            units_per_em: 1000.0,
+            ascender: 800.0,
+            line_height: 1200.0,
+            glyphs,
+            kernings: HashMap::new(),
+            fallback: '?' as u32,
+        }
+    }
+
+    fn get_baker() -> FontBaker {
+        FontBaker {
+            page_width: 128,
+            page_height: 128,
+            chars: ('A'..='Z').chain(vec!['o', ' ']).collect(),
+            ..FontBaker::new()
+        }
+    }
+
+    #[test]
+    fn test_packing() {
+        let font = get_baker().bake_vector_font(&get_vector_font()).unwrap();
+        let chars = font.chars.values().filter(|char| char.width > 0.0).collect::<Vec<&Char>>();
+
+        assert_eq!(font.chars.len(), 28);
+        assert_eq!(chars.len(), 27);
+        assert_eq!(font.distance_range, 16.0);
+        assert!(font.pages.len() > 1, "{}", font.pages.len());
+
+        for (index, a) in chars.iter().enumerate() {
+            assert!(a.page < font.pages.len());
+            assert!(a.x >= 0.0 && a.x + a.width <= font.scale_width);
+            assert!(a.y >= 0.0 && a.y + a.height <= font.scale_height);
+
+            for b in chars.iter().skip(index + 1).filter(|b| b.page == a.page) {
+                let overlaps = a.x < b.x + b.width && b.x < a.x + a.width && a.y < b.y + b.height && b.y < a.y + a.height;
+
+                assert!(!overlaps, "{:?} {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_save_fnt() {
+        let font = get_baker().bake_vector_font(&get_vector_font()).unwrap();
+        let directory = get_temp_directory("baker_save_fnt");
+
+        fs::create_dir_all(&directory).unwrap();
+        font.save_fnt(directory.join("baked.fnt")).unwrap();
+
+        let loaded = Font::from_fnt(directory.join("baked.fnt")).unwrap();
+
+        fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(loaded.size, font.size);
+        assert_eq!(loaded.line_height, font.line_height);
+        assert_eq!(loaded.base, font.base);
+        assert_eq!(loaded.distance_field, font.distance_field);
+        assert_eq!(loaded.distance_range, font.distance_range);
+        assert_eq!(loaded.pages, font.pages);
+        assert_eq!(loaded.chars.len(), font.chars.len());
+
+        for (code, char) in &font.chars {
+            let other = &loaded.chars[code];
+
+            assert_eq!(
+                (other.page, other.x, other.y, other.width, other.height, other.x_offset, other.y_offset, other.x_advance),
+                (char.page, char.x, char.y, char.width, char.height, char.x_offset, char.y_offset, char.x_advance),
+            );
+        }
+    }
+
+    #[test]
+    fn test_atlas_distance() {
+        let baker = get_baker();
+        let vector_font = get_vector_font();
+        let font = baker.bake_vector_font(&vector_font).unwrap();
+        let scale = baker.size / vector_font.units_per_em;
+
+        for &letter in &['A', 'M', 'Z', 'o'] {
+            let char = font.get_char(letter);
+            let outline = &vector_font.glyphs[&(letter as u32)].outline;
+
+            for j in 0..20 {
+                for i in 0..20 {
+                    // Relative to the glyph top-left corner, in atlas pixels
+                    let point = Vec2::new(char.width * (i as f32 + 0.37) / 20.0, char.height * (j as f32 + 0.61) / 20.0);
+                    let pixel = point + Vec2::new(char.x_offset, char.y_offset);
+                    let exact = outline.get_distance(Vec2::new(pixel.x / scale, vector_font.ascender - pixel.y / scale)) * scale;
+
+                    // The atlas saturates at the padding
+                    if exact.abs() < baker.padding - 1.0 {
+                        let distance = font.get_distance(&char, point);
+
+                        assert!((distance - exact).abs() < 0.35, "{} {:?} {} {}", letter, point, distance, exact);
+                    }
+                }
+            }
+        }
+    }
+}
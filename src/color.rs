@@ -79,6 +79,17 @@ impl Color {
         }
     }
 
+    /// Interpolates between two colors, `t` is clamped to `[0.0, 1.0]`
+    pub fn lerp(&self, color: &Color, t: f32) -> Color {
+        Color {
+            r:  lerp(self.r, color.r, t),
+            g:  lerp(self.g, color.g, t),
+            b:  lerp(self.b, color.b, t),
+            a:  lerp(self.a, color.a, t),
+            _s: (),
+        }
+    }
+
     pub fn mix_smooth(&self, color: Color, t: f32) -> Color {
         Color {
             r:  smoothstep(self.r, color.r, t),
@@ -1,7 +1,29 @@
 use bevy_math::{Vec2};
+use std::f32::consts::PI;
+
+use crate::noise::*;
 
 pub trait Distorsion {
     fn map(&self, point: Vec2) -> Vec2;
+
+    /// How much the map stretches distances around the point (the largest singular value of its Jacobian).
+    /// `Object` divides distances by it, so they don't overshoot the distorted shape.
+    /// Defaults to a finite differences estimate, distortions with a cheaper exact value override it.
+    fn get_lipschitz(&self, point: Vec2) -> f32 {
+        let h = 0.01;
+        let dx = (self.map(point + Vec2::new(h, 0.0)) - self.map(point - Vec2::new(h, 0.0))) / (2.0 * h);
+        let dy = (self.map(point + Vec2::new(0.0, h)) - self.map(point - Vec2::new(0.0, h))) / (2.0 * h);
+
+        get_max_singular_value(dx, dy)
+    }
+}
+
+/// Largest singular value of the 2x2 matrix with columns `dx` & `dy`
+pub fn get_max_singular_value(dx: Vec2, dy: Vec2) -> f32 {
+    let trace = dx.length_squared() + dy.length_squared();
+    let determinant = dx.x * dy.y - dx.y * dy.x;
+
+    ((trace + (trace * trace - 4.0 * determinant * determinant).max(0.0).sqrt()) / 2.0).sqrt()
 }
 
 pub struct Wave {
@@ -21,4 +43,441 @@ impl Distorsion for Wave {
             point.y + (self.time + (point.x / self.width) * self.y_freq).sin() * self.y_amplitude,
         )
     }
+
+    fn get_lipschitz(&self, point: Vec2) -> f32 {
+        let dx_dy = (self.time + (point.y / self.height) * self.x_freq).cos() * self.x_amplitude * self.x_freq / self.height;
+        let dy_dx = (self.time + (point.x / self.width) * self.y_freq).cos() * self.y_amplitude * self.y_freq / self.width;
+
+        get_max_singular_value(Vec2::new(1.0, dy_dx), Vec2::new(dx_dy, 1.0))
+    }
+}
+
+/// Repeats the space on an infinite grid of `spacing` size cells, centered at the origin.
+/// Shapes should fit in a cell, otherwise the distance is cut at the cell edges.
+pub struct Repeat {
+    pub spacing: Vec2,
+}
+
+impl Distorsion for Repeat {
+    fn map(&self, point: Vec2) -> Vec2 {
+        Vec2::new(repeat(point.x, self.spacing.x, f32::INFINITY), repeat(point.y, self.spacing.y, f32::INFINITY))
+    }
+
+    fn get_lipschitz(&self, _point: Vec2) -> f32 {
+        // Only moves pieces of the space around
+        1.0
+    }
+}
+
+/// Like `Repeat` but limited to `limit` copies at each side of the origin,
+/// e.g. `limit: Vec2::new(2.0, 0.0)` makes a row of 5 copies.
+pub struct RepeatFinite {
+    pub spacing: Vec2,
+    pub limit: Vec2,
+}
+
+impl Distorsion for RepeatFinite {
+    fn map(&self, point: Vec2) -> Vec2 {
+        Vec2::new(repeat(point.x, self.spacing.x, self.limit.x), repeat(point.y, self.spacing.y, self.limit.y))
+    }
+
+    fn get_lipschitz(&self, _point: Vec2) -> f32 {
+        // Only moves pieces of the space around
+        1.0
+    }
+}
+
+/// Position inside the closest cell, cells go from `-limit` to `limit`
+fn repeat(value: f32, spacing: f32, limit: f32) -> f32 {
+    if spacing <= 0.0 {
+        value
+    } else {
+        value - spacing * (value / spacing).round().clamp(-limit, limit)
+    }
+}
+
+/// Repeats the space `count` times around the origin, the first copy is the sector centered on the `+y` axis
+pub struct PolarRepeat {
+    pub count: u32,
+}
+
+impl Distorsion for PolarRepeat {
+    fn map(&self, point: Vec2) -> Vec2 {
+        let sector = 2.0 * PI / self.count.max(1) as f32;
+
+        // Angle from `+y`, clockwise, moved to the first sector
+        let angle = point.x.atan2(point.y);
+        let angle = angle - sector * (angle / sector).round();
+        let (sin, cos) = angle.sin_cos();
+
+        Vec2::new(sin, cos) * point.length()
+    }
+
+    fn get_lipschitz(&self, _point: Vec2) -> f32 {
+        // Only moves pieces of the space around
+        1.0
+    }
+}
+
+/// Mirrors the space across the line through the origin perpendicular to `angle` (degrees).
+/// Points behind the line take the shape in front, `angle: 0.0` copies the `+x` side to the `-x` side.
+pub struct Mirror {
+    pub angle: f32,
+}
+
+impl Distorsion for Mirror {
+    fn map(&self, point: Vec2) -> Vec2 {
+        let (sin, cos) = self.angle.to_radians().sin_cos();
+        let normal = Vec2::new(cos, sin);
+        let distance = point.dot(normal);
+
+        if distance < 0.0 {
+            point - normal * (2.0 * distance)
+        } else {
+            point
+        }
+    }
+
+    fn get_lipschitz(&self, _point: Vec2) -> f32 {
+        // Only moves pieces of the space around
+        1.0
+    }
+}
+
+/// Rotates the space around `center`, `angle` (degrees) at the center fading to nothing at `radius`.
+/// A `radius` of `0.0` or less leaves the space untouched.
+pub struct Swirl {
+    pub center: Vec2,
+    pub radius: f32,
+    pub angle: f32,
+}
+
+impl Distorsion for Swirl {
+    fn map(&self, point: Vec2) -> Vec2 {
+        if self.radius <= 0.0 {
+            return point;
+        }
+
+        let offset = point - self.center;
+        let falloff = (1.0 - offset.length() / self.radius).max(0.0);
+        let (sin, cos) = (self.angle.to_radians() * falloff * falloff).sin_cos();
+
+        self.center + Vec2::new(offset.x * cos - offset.y * sin, offset.x * sin + offset.y * cos)
+    }
+
+    fn get_lipschitz(&self, point: Vec2) -> f32 {
+        // Locally a shear between the radial & tangential directions: `k` is the tangential
+        // displacement per radial unit, `distance * d(angle)/d(distance)`
+        if self.radius <= 0.0 {
+            return 1.0;
+        }
+
+        let distance = (point - self.center).length();
+        let falloff = (1.0 - distance / self.radius).max(0.0);
+        let k = (distance * self.angle.to_radians() * 2.0 * falloff / self.radius).abs();
+
+        (k + (k * k + 4.0).sqrt()) / 2.0
+    }
+}
+
+/// Bends the `x` axis into an arc, `strength` is the rotation in radians per unit along `x`
+pub struct Bend {
+    pub strength: f32,
+}
+
+impl Distorsion for Bend {
+    fn map(&self, point: Vec2) -> Vec2 {
+        let (sin, cos) = (self.strength * point.x).sin_cos();
+
+        Vec2::new(cos * point.x - sin * point.y, sin * point.x + cos * point.y)
+    }
+
+    fn get_lipschitz(&self, point: Vec2) -> f32 {
+        // Rotation times `[[1 - k * y, 0], [k * x, 1]]`, the rotation doesn't stretch
+        let k = self.strength;
+
+        get_max_singular_value(Vec2::new(1.0 - k * point.y, k * point.x), Vec2::new(0.0, 1.0))
+    }
+}
+
+/// Magnifies (positive `strength`) or shrinks (negative `strength`) the space inside `radius`.
+/// `strength` should be greater than `-1.0`.
+pub struct Bulge {
+    pub center: Vec2,
+    pub radius: f32,
+    pub strength: f32,
+}
+
+impl Distorsion for Bulge {
+    fn map(&self, point: Vec2) -> Vec2 {
+        let offset = point - self.center;
+        let distance = offset.length();
+
+        if distance >= self.radius || distance == 0.0 {
+            point
+        } else {
+            self.center + offset * (distance / self.radius).powf(self.strength)
+        }
+    }
+
+    fn get_lipschitz(&self, point: Vec2) -> f32 {
+        let distance = (point - self.center).length();
+
+        if distance >= self.radius {
+            1.0
+        } else {
+            // Radial stretch is `(1 + strength) * scale`, tangential is `scale`
+            let scale = (distance.max(f32::EPSILON) / self.radius).powf(self.strength);
+            scale * (1.0 + self.strength).max(1.0)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NoiseType {
+    Value,
+    Perlin,
+    Simplex,
+}
+
+impl NoiseType {
+    pub fn get_function(&self) -> fn(Vec2, u32) -> f32 {
+        match self {
+            NoiseType::Value => value,
+            NoiseType::Perlin => perlin,
+            NoiseType::Simplex => simplex,
+        }
+    }
+
+    pub fn get_gradient_bound(&self) -> f32 {
+        match self {
+            NoiseType::Value => VALUE_GRADIENT_BOUND,
+            NoiseType::Perlin => PERLIN_GRADIENT_BOUND,
+            NoiseType::Simplex => SIMPLEX_GRADIENT_BOUND,
+        }
+    }
+
+    /// Bound of the stretch of displacing the space by `amplitude` times `octaves` of this noise (one per axis),
+    /// sampled at `frequency`. `slope` is the fractal sum slope compared to a single octave.
+    fn get_lipschitz(&self, amplitude: f32, frequency: f32, slope: f32) -> f32 {
+        // `I + J`, where each row of `J` is at most `amplitude * frequency * slope * bound` long
+        1.0 + (amplitude * frequency).abs() * slope * self.get_gradient_bound() * std::f32::consts::SQRT_2
+    }
+}
+
+/// Displaces the space with fractal noise, up to `amplitude` in each axis.
+/// `frequency` is the number of noise cells per unit and `time` scrolls the noise.
+pub struct Noise {
+    pub noise: NoiseType,
+    pub amplitude: f32,
+    pub frequency: f32,
+    pub octaves: u32,
+    pub seed: u32,
+    pub time: f32,
+}
+
+impl Distorsion for Noise {
+    fn map(&self, point: Vec2) -> Vec2 {
+        let noise = self.noise.get_function();
+        let sample = point * self.frequency + Vec2::new(self.time, self.time);
+
+        // Different seeds per axis, otherwise the displacement would be always diagonal
+        point + Vec2::new(
+            fbm(noise, sample, self.seed, self.octaves),
+            fbm(noise, sample, self.seed.wrapping_add(1013), self.octaves),
+        ) * self.amplitude
+    }
+
+    fn get_lipschitz(&self, _point: Vec2) -> f32 {
+        self.noise.get_lipschitz(self.amplitude, self.frequency, get_fractal_gradient_scale(self.octaves))
+    }
+}
+
+/// Like `Noise` with turbulence (absolute value octaves), which gives sharper & swirlier creases
+pub struct Turbulence {
+    pub noise: NoiseType,
+    pub amplitude: f32,
+    pub frequency: f32,
+    pub octaves: u32,
+    pub seed: u32,
+    pub time: f32,
+}
+
+impl Distorsion for Turbulence {
+    fn map(&self, point: Vec2) -> Vec2 {
+        let noise = self.noise.get_function();
+        let sample = point * self.frequency + Vec2::new(self.time, self.time);
+
+        // Turbulence is in [0.0, 1.0], center it so the shape doesn't drift
+        point + Vec2::new(
+            turbulence(noise, sample, self.seed, self.octaves) * 2.0 - 1.0,
+            turbulence(noise, sample, self.seed.wrapping_add(1013), self.octaves) * 2.0 - 1.0,
+        ) * self.amplitude
+    }
+
+    fn get_lipschitz(&self, _point: Vec2) -> f32 {
+        // The absolute value keeps the slope, `* 2.0` doubles it
+        self.noise.get_lipschitz(self.amplitude, self.frequency, 2.0 * get_fractal_gradient_scale(self.octaves))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_point(point: Vec2, expected: Vec2) {
+        assert!((point - expected).length() < 1e-4, "{:?} != {:?}", point, expected);
+    }
+
+    #[test]
+    fn test_repeat() {
+        let repeat = Repeat { spacing: Vec2::new(10.0, 4.0) };
+
+        assert_point(repeat.map(Vec2::new(1.0, 1.0)), Vec2::new(1.0, 1.0));
+        assert_point(repeat.map(Vec2::new(23.0, -9.0)), Vec2::new(3.0, -1.0));
+        assert_point(repeat.map(Vec2::new(-6.0, 1.0)), Vec2::new(4.0, 1.0));
+
+        let repeat = RepeatFinite { spacing: Vec2::new(10.0, 4.0), limit: Vec2::new(2.0, 0.0) };
+
+        assert_point(repeat.map(Vec2::new(23.0, 1.0)), Vec2::new(3.0, 1.0));
+        assert_point(repeat.map(Vec2::new(-43.0, 1.0)), Vec2::new(-23.0, 1.0));
+        assert_point(repeat.map(Vec2::new(0.0, 9.0)), Vec2::new(0.0, 9.0));
+    }
+
+    #[test]
+    fn test_polar_repeat() {
+        let repeat = PolarRepeat { count: 4 };
+
+        assert_point(repeat.map(Vec2::new(0.0, 5.0)), Vec2::new(0.0, 5.0));
+        assert_point(repeat.map(Vec2::new(5.0, 0.0)), Vec2::new(0.0, 5.0));
+        assert_point(repeat.map(Vec2::new(0.0, -5.0)), Vec2::new(0.0, 5.0));
+        assert_point(repeat.map(Vec2::new(-5.0, 1.0)), Vec2::new(1.0, 5.0));
+        assert_point(repeat.map(Vec2::zero()), Vec2::zero());
+    }
+
+    #[test]
+    fn test_swirl_and_bend() {
+        let swirl = Swirl { center: Vec2::new(1.0, 1.0), radius: 2.0, angle: 90.0 };
+
+        assert_point(swirl.map(Vec2::new(1.0, 1.0)), Vec2::new(1.0, 1.0));
+        assert_point(swirl.map(Vec2::new(4.0, 1.0)), Vec2::new(4.0, 1.0));
+        // Half way, a quarter of the angle
+        let point = swirl.map(Vec2::new(2.0, 1.0));
+        assert!(((point.y - 1.0).atan2(point.x - 1.0).to_degrees() - 22.5).abs() < 1e-3);
+        assert!(((point - Vec2::new(1.0, 1.0)).length() - 1.0).abs() < 1e-5);
+
+        // No radius, no swirl
+        for &radius in &[0.0, -2.0] {
+            let swirl = Swirl { center: Vec2::new(1.0, 1.0), radius, angle: 90.0 };
+
+            assert_eq!(swirl.map(Vec2::new(1.0, 1.0)), Vec2::new(1.0, 1.0));
+            assert_eq!(swirl.map(Vec2::new(2.0, 3.0)), Vec2::new(2.0, 3.0));
+            assert_eq!(swirl.get_lipschitz(Vec2::new(1.0, 1.0)), 1.0);
+        }
+
+        let bend = Bend { strength: 0.5 };
+
+        assert_point(bend.map(Vec2::new(0.0, 3.0)), Vec2::new(0.0, 3.0));
+        assert_point(Bend { strength: 0.0 }.map(Vec2::new(2.0, 3.0)), Vec2::new(2.0, 3.0));
+        assert!((bend.map(Vec2::new(2.0, 1.0)).length() - 5.0f32.sqrt()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_bulge() {
+        let bulge = Bulge { center: Vec2::zero(), radius: 4.0, strength: 1.0 };
+
+        assert_point(bulge.map(Vec2::zero()), Vec2::zero());
+        assert_point(bulge.map(Vec2::new(0.0, 5.0)), Vec2::new(0.0, 5.0));
+        assert_point(bulge.map(Vec2::new(2.0, 0.0)), Vec2::new(1.0, 0.0));
+
+        let pinch = Bulge { center: Vec2::zero(), radius: 4.0, strength: -0.5 };
+
+        assert_point(pinch.map(Vec2::new(1.0, 0.0)), Vec2::new(2.0, 0.0));
+    }
+
+    #[test]
+    fn test_noise() {
+        for &noise in &[NoiseType::Perlin, NoiseType::Simplex] {
+            let distortion = Noise { noise, amplitude: 3.0, frequency: 0.1, octaves: 4, seed: 42, time: 0.0 };
+            let turbulence = Turbulence { noise, amplitude: 3.0, frequency: 0.1, octaves: 4, seed: 42, time: 0.0 };
+            let mut total = 0.0;
+
+            for i in 0..500 {
+                let point = Vec2::new(i as f32 * 1.3, i as f32 * -0.7);
+                let offset = distortion.map(point) - point;
+
+                assert!(offset.x.abs() <= 3.0 && offset.y.abs() <= 3.0);
+                assert!((turbulence.map(point) - point).abs().max_element() <= 3.0);
+                assert_eq!(distortion.map(point), distortion.map(point));
+                total += offset.length();
+            }
+
+            assert!(total > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_lipschitz() {
+        let distortions: Vec<Box<dyn Distorsion>> = vec![
+            Box::new(Wave { width: 600.0, height: 600.0, x_amplitude: 20.0, x_freq: 100.0, y_amplitude: 5.0, y_freq: 30.0, time: 1.0 }),
+            Box::new(Swirl { center: Vec2::new(1.0, 1.0), radius: 20.0, angle: 120.0 }),
+            Box::new(Bend { strength: 0.05 }),
+            Box::new(Bulge { center: Vec2::zero(), radius: 20.0, strength: 0.6 }),
+            Box::new(Bulge { center: Vec2::zero(), radius: 20.0, strength: -0.4 }),
+        ];
+        let numeric = |distortion: &dyn Distorsion, point: Vec2| {
+            let h = 0.001;
+            let dx = (distortion.map(point + Vec2::new(h, 0.0)) - distortion.map(point - Vec2::new(h, 0.0))) / (2.0 * h);
+            let dy = (distortion.map(point + Vec2::new(0.0, h)) - distortion.map(point - Vec2::new(0.0, h))) / (2.0 * h);
+
+            get_max_singular_value(dx, dy)
+        };
+
+        // Analytic values match the finite differences
+        for distortion in &distortions {
+            for i in 0..50 {
+                let point = Vec2::new(i as f32 * 0.7 - 17.0, i as f32 * -0.3 + 8.0);
+                let expected = numeric(distortion.as_ref(), point);
+
+                assert!((distortion.get_lipschitz(point) - expected).abs() < expected * 0.02, "{} != {}", distortion.get_lipschitz(point), expected);
+            }
+        }
+
+        assert_eq!(get_max_singular_value(Vec2::new(2.0, 0.0), Vec2::new(0.0, 0.5)), 2.0);
+        assert_eq!(Mirror { angle: 30.0 }.get_lipschitz(Vec2::zero()), 1.0);
+
+        // Noises give a global bound, it's never below the local stretch
+        for &noise in &[NoiseType::Value, NoiseType::Perlin, NoiseType::Simplex] {
+            for &octaves in &[1, 4] {
+                let distortions: [Box<dyn Distorsion>; 2] = [
+                    Box::new(Noise { noise, amplitude: 10.0, frequency: 0.1, octaves, seed: 0, time: 0.3 }),
+                    Box::new(Turbulence { noise, amplitude: 10.0, frequency: 0.1, octaves, seed: 0, time: 0.3 }),
+                ];
+
+                for distortion in &distortions {
+                    let bound = distortion.get_lipschitz(Vec2::zero());
+
+                    for i in 0..2000 {
+                        let point = Vec2::new((i % 50) as f32 * 0.61 - 15.0, (i / 50) as f32 * 0.83 - 17.0);
+
+                        assert_eq!(distortion.get_lipschitz(point), bound);
+                        assert!(numeric(distortion.as_ref(), point) <= bound * 1.001, "{:?} {}", noise, octaves);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_mirror() {
+        let mirror = Mirror { angle: 0.0 };
+
+        assert_point(mirror.map(Vec2::new(-3.0, 2.0)), Vec2::new(3.0, 2.0));
+        assert_point(mirror.map(Vec2::new(3.0, 2.0)), Vec2::new(3.0, 2.0));
+
+        let mirror = Mirror { angle: 45.0 };
+
+        assert_point(mirror.map(Vec2::new(-2.0, 0.0)), Vec2::new(0.0, 2.0));
+        assert_point(mirror.map(Vec2::new(1.0, 1.0)), Vec2::new(1.0, 1.0));
+    }
 }
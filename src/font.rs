@@ -1,8 +1,25 @@
-use std::{collections::HashMap, fs::File};
+use bevy_math::Vec2;
+use std::{collections::HashMap, convert::TryFrom, error::Error, fmt::Write as _, fs, fs::File, io::BufWriter, path::Path};
+use ttf_parser as ttf;
+
+use crate::outline::Outline;
+use crate::utils::*;
+
+/// How distances are encoded in the atlas
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DistanceField {
+    /// Single channel SDF, stored in the alpha channel (Hiero)
+    Sdf,
+    /// Multi-channel SDF, distance is the median of the RGB channels (msdfgen)
+    Msdf,
+    /// Multi-channel SDF plus a true SDF in the alpha channel, used away from the glyph edges
+    Mtsdf,
+}
 
 #[derive(Clone, Debug)]
 pub struct Char {
-    pub code: u8,
+    pub code: u32,
+    pub page: usize,
     pub width: f32,
     pub height: f32,
     pub x: f32,
@@ -19,1107 +36,684 @@ pub struct Font {
     pub base: f32,
     pub scale_width: f32,
     pub scale_height: f32,
-    pub chars: HashMap<u8, Char>,
-    pub image: Vec<u8>,
+    /// Glyphs keyed by their Unicode code point
+    pub chars: HashMap<u32, Char>,
+    /// Extra x-advance between a pair of chars, keyed by `(first, second)` code points
+    pub kernings: HashMap<(u32, u32), f32>,
+    /// Code point of the glyph used for chars missing in the font
+    pub fallback: u32,
+    pub distance_field: DistanceField,
+    /// Width of the distance gradient in atlas pixels, the glyph edge is at its center
+    pub distance_range: f32,
+    /// RGBA atlas images, indexed by `Char::page`. All of them are `scale_width` x `scale_height`.
+    pub pages: Vec<Vec<u8>>,
 }
 
 impl Font {
     pub fn get_char(&self, letter: char) -> Char {
-        let placeholder = self.chars.get(&63); // '?' character
-        let char = self.chars.get(&(letter as u8));
+        let char = self.chars.get(&(letter as u32));
 
-        match char {
+        match char.or_else(|| self.chars.get(&self.fallback)) {
             Some(char) => char.clone(),
-            None => placeholder.unwrap().clone()
+            // Not even the fallback exists, use an empty glyph
+            None => Char {
+                code: letter as u32,
+                page: 0,
+                width: 0.0,
+                height: 0.0,
+                x: 0.0,
+                x_advance: 0.0,
+                x_offset: 0.0,
+                y: 0.0,
+                y_offset: 0.0,
+            },
         }
     }
+
+    pub fn get_kerning(&self, first: char, second: char) -> f32 {
+        *self.kernings.get(&(first as u32, second as u32)).unwrap_or(&0.0)
+    }
+
+    /// Bilinear-interpolated RGBA sample of a page, channels mapped to `[0.0, 1.0]`
+    fn sample(&self, page: usize, point: Vec2) -> [f32; 4] {
+        let width = self.scale_width as usize;
+        let height = self.scale_height as usize;
+        let image = &self.pages[page];
+
+        // Points past the edges take the edge pixels
+        let point = point.max(Vec2::zero()).min(Vec2::new((width - 1) as f32, (height - 1) as f32));
+
+        // Top-left sample position on the buffer, kept one pixel away from the
+        // right/bottom image edges (weights still add up for points on the edge)
+        let x = (point.x.floor() as usize).min(width - 2);
+        let y = (point.y.floor() as usize).min(height - 2);
+
+        // Calculate the weights for each sample
+        let a_weight = ((x + 1) as f32 - point.x) * ((y + 1) as f32 - point.y); // Top-left
+        let b_weight = (point.x - (x as f32)) * ((y + 1) as f32 - point.y);     // Top-right
+        let c_weight = ((x + 1) as f32 - point.x) * (point.y - y as f32);       // Bot-left
+        let d_weight = (point.x - (x as f32)) * (point.y - y as f32);           // Bot-right
+
+        let mut value = [0.0; 4];
+
+        for (channel, value) in value.iter_mut().enumerate() {
+            let a = image[(y       * width + x) * 4 + channel] as f32;
+            let b = image[(y       * width + x + 1) * 4 + channel] as f32;
+            let c = image[((y + 1) * width + x) * 4 + channel] as f32;
+            let d = image[((y + 1) * width + x + 1) * 4 + channel] as f32;
+
+            *value = ((a * a_weight) + (b * b_weight) + (c * c_weight) + (d * d_weight)) / 255.0;
+        }
+
+        value
+    }
+
+    /// Signed distance to the glyph edge, `point` is relative to the glyph top-left corner.
+    /// Distances are in atlas pixels and saturate at `±distance_range / 2.0`.
+    pub fn get_distance(&self, char: &Char, point: Vec2) -> f32 {
+        let [r, g, b, a] = self.sample(char.page, point + Vec2::new(char.x, char.y));
+        let to_distance = |value: f32| (0.5 - value) * self.distance_range; // 0.5 is the glyph edge
+
+        match self.distance_field {
+            DistanceField::Sdf => to_distance(a),
+            DistanceField::Msdf => to_distance(median(r, g, b)),
+            DistanceField::Mtsdf => {
+                let distance = to_distance(median(r, g, b));
+                let true_distance = to_distance(a);
+
+                // Median keeps corners sharp near the edge, but has artifacts away from it
+                let t = smoothstep(self.distance_range * 0.125, self.distance_range * 0.25, true_distance.abs());
+                lerp(distance, true_distance, t)
+            }
+        }
+    }
+}
+
+/// Glyph outline of a `VectorFont`, in font units
+#[derive(Clone, Debug)]
+pub struct VectorGlyph {
+    pub code: u32,
+    pub x_advance: f32,
+    /// Y axis points up, origin at the baseline
+    pub outline: Outline,
 }
 
-fn load_png(path: String) -> (Vec<u8>, u32, u32) {
+/// TrueType/OpenType glyph outlines, for exact vector text. All metrics are in font units.
+#[derive(Debug)]
+pub struct VectorFont {
+    pub units_per_em: f32,
+    pub ascender: f32,
+    pub line_height: f32,
+    /// Glyphs keyed by their Unicode code point
+    pub glyphs: HashMap<u32, VectorGlyph>,
+    /// Extra x-advance between a pair of chars, keyed by `(first, second)` code points
+    pub kernings: HashMap<(u32, u32), f32>,
+    /// Code point of the glyph used for chars missing in the font
+    pub fallback: u32,
+}
+
+impl VectorFont {
+    /// Loads the outlines of `chars` from a `*.ttf`/`*.otf` file
+    pub fn from_ttf<P: AsRef<Path>>(path: P, chars: &[char]) -> Result<VectorFont, Box<dyn Error>> {
+        VectorFont::from_data(&fs::read(path)?, chars)
+    }
+
+    pub fn from_data(data: &[u8], chars: &[char]) -> Result<VectorFont, Box<dyn Error>> {
+        let face = ttf::Font::from_data(data, 0).ok_or("unable to parse font file")?;
+        let ascender = face.ascender() as f32;
+        let mut font = VectorFont {
+            units_per_em: face.units_per_em().ok_or("font has no units per em")? as f32,
+            ascender,
+            line_height: ascender - face.descender() as f32 + face.line_gap() as f32,
+            glyphs: HashMap::new(),
+            kernings: HashMap::new(),
+            fallback: '?' as u32,
+        };
+
+        for &letter in chars {
+            let glyph_id = match face.glyph_index(letter) {
+                Some(glyph_id) => glyph_id,
+                None => continue,
+            };
+            let mut outline = Outline::new();
+
+            // Glyphs without outline (e.g. space) only advance the cursor
+            if face.outline_glyph(glyph_id, &mut outline).is_none() {
+                outline = Outline::new();
+            }
+
+            font.glyphs.insert(letter as u32, VectorGlyph {
+                code: letter as u32,
+                x_advance: face.glyph_hor_advance(glyph_id).unwrap_or(0) as f32,
+                outline,
+            });
+        }
+
+        // Kerning pairs from the `kern` table
+        for subtable in face.kerning_subtables() {
+            if !subtable.is_horizontal() || subtable.is_variable() || subtable.has_cross_stream() {
+                continue;
+            }
+
+            for &first in chars {
+                for &second in chars {
+                    if let (Some(left), Some(right)) = (face.glyph_index(first), face.glyph_index(second)) {
+                        if let Some(amount) = subtable.glyphs_kerning(left, right) {
+                            font.kernings.insert((first as u32, second as u32), amount as f32);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(font)
+    }
+
+    pub fn get_glyph(&self, letter: char) -> Option<&VectorGlyph> {
+        self.glyphs.get(&(letter as u32)).or_else(|| self.glyphs.get(&self.fallback))
+    }
+
+    pub fn get_kerning(&self, first: char, second: char) -> f32 {
+        *self.kernings.get(&(first as u32, second as u32)).unwrap_or(&0.0)
+    }
+}
+
+/// Loads a PNG as an 8-bit RGBA buffer
+pub fn load_png<P: AsRef<Path>>(path: P) -> Result<(Vec<u8>, u32, u32), Box<dyn Error>> {
     // The decoder is a build for reader and can be used to set various decoding options
     // via `Transformations`. The default output transformation is `Transformations::EXPAND
-    // | Transformations::STRIP_ALPHA`.
-    let decoder = png::Decoder::new(File::open(path).unwrap());
-    let (info, mut reader) = decoder.read_info().unwrap();
+    // | Transformations::SCALE_16 | Transformations::STRIP_16`.
+    let decoder = png::Decoder::new(File::open(path)?);
+    let (info, mut reader) = decoder.read_info()?;
     // Allocate the output buffer.
     let mut buf = vec![0; info.buffer_size()];
     // Read the next frame. An APNG might contain multiple frames.
-    reader.next_frame(&mut buf).unwrap();
+    reader.next_frame(&mut buf)?;
+
+    // Expand everything to RGBA
+    let image = match reader.output_color_type().0 {
+        png::ColorType::RGBA => buf,
+        png::ColorType::RGB => buf.chunks(3).flat_map(|c| vec![c[0], c[1], c[2], 0xFF]).collect(),
+        png::ColorType::GrayscaleAlpha => buf.chunks(2).flat_map(|c| vec![c[0], c[0], c[0], c[1]]).collect(),
+        png::ColorType::Grayscale => buf.iter().flat_map(|&c| vec![c, c, c, 0xFF]).collect(),
+        png::ColorType::Indexed => return Err("indexed PNGs are not supported".into()),
+    };
 
-    // Only use alpha channel, discard the others
-    let image = buf.chunks(4).map(|chunk| chunk[3]).collect::<Vec<u8>>();
+    Ok((image, info.width, info.height))
+}
 
-    (image, info.width, info.height)
+/// Saves an 8-bit RGBA buffer as PNG
+fn save_png<P: AsRef<Path>>(path: P, image: &[u8], width: u32, height: u32) -> Result<(), Box<dyn Error>> {
+    let mut encoder = png::Encoder::new(BufWriter::new(File::create(path)?), width, height);
+    encoder.set_color(png::ColorType::RGBA);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(image)?;
+
+    Ok(())
 }
 
-pub fn get_comic_sans() -> Font {
-    let (image, width, height) = load_png(String::from("comic-sans.png"));
-    let mut comic_sans = Font {
-        size: 32.0,
-        line_height: 46.0,
-        base: 36.0,
-        scale_width: width as f32,
-        scale_height: height as f32,
-        chars: HashMap::new(),
-        image: image,
+/// Splits a BMFont text line into its tag and `key=value` attributes.
+/// Values can be quoted (`file="comic sans.png"`), quotes are removed.
+fn parse_line(line: &str) -> (&str, HashMap<&str, &str>) {
+    let line = line.trim();
+    let (tag, mut rest) = match line.find(char::is_whitespace) {
+        Some(index) => (&line[..index], line[index..].trim_start()),
+        None => (line, ""),
     };
+    let mut attributes = HashMap::new();
+
+    while let Some(equal) = rest.find('=') {
+        let key = rest[..equal].trim();
+        let value_start = &rest[equal + 1..];
+
+        let (value, remaining) = if let Some(quoted) = value_start.strip_prefix('"') {
+            match quoted.find('"') {
+                Some(end) => (&quoted[..end], &quoted[end + 1..]),
+                None => (quoted, ""),
+            }
+        } else {
+            match value_start.find(char::is_whitespace) {
+                Some(end) => (&value_start[..end], &value_start[end..]),
+                None => (value_start, ""),
+            }
+        };
+
+        attributes.insert(key, value);
+        rest = remaining.trim_start();
+    }
+
+    (tag, attributes)
+}
+
+fn get_attribute<'a>(attributes: &HashMap<&str, &'a str>, key: &str, line: usize) -> Result<&'a str, Box<dyn Error>> {
+    attributes
+        .get(key)
+        .copied()
+        .ok_or_else(|| format!("line {}: missing `{}` attribute", line, key).into())
+}
+
+fn get_number<T: std::str::FromStr>(attributes: &HashMap<&str, &str>, key: &str, line: usize) -> Result<T, Box<dyn Error>> {
+    let value = get_attribute(attributes, key, line)?;
+
+    value
+        .parse::<T>()
+        .map_err(|_| format!("line {}: invalid `{}` value `{}`", line, key, value).into())
+}
+
+impl Font {
+    /// Loads an AngelCode BMFont text file (`*.fnt`), like the ones exported by Hiero.
+    /// The page image path is resolved relative to the `*.fnt` file.
+    ///
+    /// File format: http://www.angelcode.com/products/bmfont/doc/file_format.html
+    pub fn from_fnt<P: AsRef<Path>>(path: P) -> Result<Font, Box<dyn Error>> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path)?;
+        let mut page_files: Vec<(usize, String)> = vec![];
+        let mut page_count = 0;
+        let mut font = Font {
+            size: 0.0,
+            line_height: 0.0,
+            base: 0.0,
+            scale_width: 0.0,
+            scale_height: 0.0,
+            chars: HashMap::new(),
+            kernings: HashMap::new(),
+            fallback: '?' as u32,
+            distance_field: DistanceField::Sdf,
+            distance_range: 0.0,
+            pages: vec![],
+        };
+
+        for (index, line) in source.lines().enumerate() {
+            let line_number = index + 1;
+            let (tag, attributes) = parse_line(line);
+
+            match tag {
+                "info" => {
+                    // Negative sizes mean "match char height" instead of "match cell height"
+                    font.size = get_number::<f32>(&attributes, "size", line_number)?.abs();
+
+                    // Hiero pads glyphs with the SDF spread: `padding=up,right,down,left`
+                    if font.distance_range == 0.0 {
+                        let padding = get_attribute(&attributes, "padding", line_number)?;
+                        let padding = padding.split(',').next().unwrap_or("");
+
+                        font.distance_range = padding
+                            .parse::<f32>()
+                            .map_err(|_| format!("line {}: invalid `padding` value `{}`", line_number, padding))?
+                            * 2.0;
+                    }
+                }
+                // Written by msdf-bmfont-xml, it takes precedence over `info padding`
+                "distanceField" => {
+                    font.distance_field = match get_attribute(&attributes, "fieldType", line_number)? {
+                        "sdf" | "psdf" => DistanceField::Sdf,
+                        "msdf" => DistanceField::Msdf,
+                        "mtsdf" => DistanceField::Mtsdf,
+                        field_type => return Err(format!("line {}: unknown `fieldType` `{}`", line_number, field_type).into()),
+                    };
+                    font.distance_range = get_number(&attributes, "distanceRange", line_number)?;
+                }
+                "common" => {
+                    font.line_height = get_number(&attributes, "lineHeight", line_number)?;
+                    font.base = get_number(&attributes, "base", line_number)?;
+                    font.scale_width = get_number(&attributes, "scaleW", line_number)?;
+                    font.scale_height = get_number(&attributes, "scaleH", line_number)?;
+                    page_count = get_number(&attributes, "pages", line_number)?;
+                }
+                "page" => {
+                    let id: usize = get_number(&attributes, "id", line_number)?;
+
+                    page_files.push((id, get_attribute(&attributes, "file", line_number)?.to_string()));
+                }
+                "char" => {
+                    // BMFont uses `id=-1` for its "invalid char" glyph, skip anything that isn't a code point
+                    let id: i64 = get_number(&attributes, "id", line_number)?;
+
+                    if let Ok(code) = u32::try_from(id) {
+                        font.chars.insert(code, Char {
+                            code,
+                            page: get_number(&attributes, "page", line_number)?,
+                            width: get_number(&attributes, "width", line_number)?,
+                            height: get_number(&attributes, "height", line_number)?,
+                            x: get_number(&attributes, "x", line_number)?,
+                            x_advance: get_number(&attributes, "xadvance", line_number)?,
+                            x_offset: get_number(&attributes, "xoffset", line_number)?,
+                            y: get_number(&attributes, "y", line_number)?,
+                            y_offset: get_number(&attributes, "yoffset", line_number)?,
+                        });
+                    }
+                }
+                "kerning" => {
+                    let first: u32 = get_number(&attributes, "first", line_number)?;
+                    let second: u32 = get_number(&attributes, "second", line_number)?;
+
+                    font.kernings.insert((first, second), get_number(&attributes, "amount", line_number)?);
+                }
+                // Counters are not needed to fill the `Font`
+                "chars" | "kernings" | "" => (),
+                _ => return Err(format!("line {}: unknown tag `{}`", line_number, tag).into()),
+            }
+        }
+
+        if font.distance_range <= 0.0 {
+            return Err("missing distance range, set it with `info padding` or `distanceField distanceRange`".into());
+        }
+
+        if page_files.is_empty() {
+            return Err("missing `page` line".into());
+        }
+
+        // Validated once the whole file is read, the lines can come in any order
+        if page_files.len() != page_count {
+            return Err(format!("`common pages={}` doesn't match the {} `page` lines", page_count, page_files.len()).into());
+        }
+
+        if let Some((id, _)) = page_files.iter().find(|(id, _)| *id >= page_count) {
+            return Err(format!("page id `{}` is out of range, there are {} pages", id, page_count).into());
+        }
+
+        // The first page sets the size the others are checked against
+        page_files.sort_by_key(|(id, _)| *id);
+
+        let directory = path.parent().unwrap_or_else(|| Path::new(""));
+        font.pages = vec![vec![]; page_count];
+
+        for (id, file) in page_files {
+            if !font.pages[id].is_empty() {
+                return Err(format!("page id `{}` is repeated", id).into());
+            }
+
+            let (image, width, height) = load_png(directory.join(&file))?;
+
+            // Bilinear sampling needs, at least, 2x2 pixels
+            if width < 2 || height < 2 {
+                return Err(format!("page `{}` is smaller than 2x2 pixels", file).into());
+            }
+
+            // Trust the image dimensions over `scaleW`/`scaleH`, they're used to index the buffer
+            if id == 0 {
+                font.scale_width = width as f32;
+                font.scale_height = height as f32;
+            } else if width as f32 != font.scale_width || height as f32 != font.scale_height {
+                return Err(format!("page `{}` size doesn't match the first page", file).into());
+            }
+
+            font.pages[id] = image;
+        }
+
+        if font.chars.values().any(|char| !matches!(font.pages.get(char.page), Some(page) if !page.is_empty())) {
+            return Err("char references a missing page".into());
+        }
+
+        Ok(font)
+    }
+
+    /// Saves the font as a BMFont text file, pages are stored next to it as `<name>_<page>.png`
+    pub fn save_fnt<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        let path = path.as_ref();
+        let name = path.file_stem().and_then(|stem| stem.to_str()).ok_or("invalid `*.fnt` path")?;
+        let directory = path.parent().unwrap_or_else(|| Path::new(""));
+        let padding = self.distance_range / 2.0;
+        let field_type = match self.distance_field {
+            DistanceField::Sdf => "sdf",
+            DistanceField::Msdf => "msdf",
+            DistanceField::Mtsdf => "mtsdf",
+        };
+        let mut fnt = String::new();
+
+        writeln!(fnt, "info face=\"{}\" size={} unicode=1 padding={},{},{},{}", name, self.size, padding, padding, padding, padding)?;
+        writeln!(fnt, "common lineHeight={} base={} scaleW={} scaleH={} pages={}", self.line_height, self.base, self.scale_width, self.scale_height, self.pages.len())?;
+
+        for (id, page) in self.pages.iter().enumerate() {
+            let file = format!("{}_{}.png", name, id);
+
+            save_png(directory.join(&file), page, self.scale_width as u32, self.scale_height as u32)?;
+            writeln!(fnt, "page id={} file=\"{}\"", id, file)?;
+        }
+
+        writeln!(fnt, "distanceField fieldType={} distanceRange={}", field_type, self.distance_range)?;
+
+        // Sorted output, `HashMap` order is random
+        let mut chars = self.chars.values().collect::<Vec<&Char>>();
+        chars.sort_by_key(|char| char.code);
+
+        writeln!(fnt, "chars count={}", chars.len())?;
+
+        for char in chars {
+            writeln!(
+                fnt,
+                "char id={} x={} y={} width={} height={} xoffset={} yoffset={} xadvance={} page={} chnl=15",
+                char.code, char.x, char.y, char.width, char.height, char.x_offset, char.y_offset, char.x_advance, char.page,
+            )?;
+        }
+
+        let mut kernings = self.kernings.iter().collect::<Vec<(&(u32, u32), &f32)>>();
+        kernings.sort_by_key(|(pair, _)| **pair);
+
+        writeln!(fnt, "kernings count={}", kernings.len())?;
+
+        for ((first, second), amount) in kernings {
+            writeln!(fnt, "kerning first={} second={} amount={}", first, second, amount)?;
+        }
+
+        fs::write(path, fnt)?;
 
-    // Space
-    comic_sans.chars.insert(32, Char {
-        code: 32,
-        width: 0.0,
-        height: 0.0,
-        x: 0.0,
-        x_advance: 12.0,
-        x_offset: 0.0,
-        y: 0.0,
-        y_offset: 0.0,
-    });
-
-    comic_sans.chars.insert(10, Char {
-        code: 10,
-        width: 0.0,
-        height: 0.0,
-        x: 0.0,
-        x_advance: 10.0,
-        x_offset: -8.0,
-        y: 0.0,
-        y_offset: 0.0,
-    });
-
-    comic_sans.chars.insert(33, Char {
-        code: 33,
-        width: 21.0,
-        height: 44.0,
-        x: 419.0,
-        x_advance: 8.0,
-        x_offset: -7.0,
-        y: 0.0,
-        y_offset: 2.0,
-    });
-
-    comic_sans.chars.insert(34, Char {
-        code: 34,
-        width: 26.0,
-        height: 27.0,
-        x: 230.0,
-        x_advance: 14.0,
-        x_offset: -7.0,
-        y: 218.0,
-        y_offset: 3.0,
-    });
-
-    comic_sans.chars.insert(35, Char {
-        code: 35,
-        width: 43.0,
-        height: 42.0,
-        x: 135.0,
-        x_advance: 27.0,
-        x_offset: -8.0,
-        y: 135.0,
-        y_offset: 3.0,
-    });
-
-    comic_sans.chars.insert(36, Char {
-        code: 36,
-        width: 35.0,
-        height: 50.0,
-        x: 28.0,
-        x_advance: 22.0,
-        x_offset: -7.0,
-        y: 0.0,
-        y_offset: 1.0,
-    });
-
-    comic_sans.chars.insert(37, Char {
-        code: 37,
-        width: 40.0,
-        height: 43.0,
-        x: 359.0,
-        x_advance: 26.0,
-        x_offset: -6.0,
-        y: 50.0,
-        y_offset: 2.0,
-    });
-
-    comic_sans.chars.insert(38, Char {
-        code: 38,
-        width: 35.0,
-        height: 43.0,
-        x: 399.0,
-        x_advance: 21.0,
-        x_offset: -7.0,
-        y: 50.0,
-        y_offset: 3.0,
-    });
-
-    comic_sans.chars.insert(39, Char {
-        code: 39,
-        width: 20.0,
-        height: 25.0,
-        x: 256.0,
-        x_advance: 12.0,
-        x_offset: -4.0,
-        y: 218.0,
-        y_offset: 2.0,
-    });
-
-    comic_sans.chars.insert(40, Char {
-        code: 40,
-        width: 26.0,
-        height: 49.0,
-        x: 63.0,
-        x_advance: 12.0,
-        x_offset: -7.0,
-        y: 0.0,
-        y_offset: 2.0,
-    });
-
-    comic_sans.chars.insert(41, Char {
-        code: 41,
-        width: 26.0,
-        height: 49.0,
-        x: 89.0,
-        x_advance: 12.0,
-        x_offset: -7.0,
-        y: 0.0,
-        y_offset: 2.0,
-    });
-
-    comic_sans.chars.insert(42, Char {
-        code: 42,
-        width: 32.0,
-        height: 29.0,
-        x: 169.0,
-        x_advance: 17.0,
-        x_offset: -8.0,
-        y: 218.0,
-        y_offset: 3.0,
-    });
-
-    comic_sans.chars.insert(43, Char {
-        code: 43,
-        width: 31.0,
-        height: 30.0,
-        x: 138.0,
-        x_advance: 15.0,
-        x_offset: -8.0,
-        y: 218.0,
-        y_offset: 11.0,
-    });
-
-    comic_sans.chars.insert(44, Char {
-        code: 44,
-        width: 21.0,
-        height: 25.0,
-        x: 276.0,
-        x_advance: 9.0,
-        x_offset: -5.0,
-        y: 218.0,
-        y_offset: 25.0,
-    });
-
-    comic_sans.chars.insert(45, Char {
-        code: 45,
-        width: 27.0,
-        height: 19.0,
-        x: 442.0,
-        x_advance: 13.0,
-        x_offset: -7.0,
-        y: 218.0,
-        y_offset: 18.0,
-    });
-
-    comic_sans.chars.insert(46, Char {
-        code: 46,
-        width: 21.0,
-        height: 21.0,
-        x: 383.0,
-        x_advance: 8.0,
-        x_offset: -6.0,
-        y: 218.0,
-        y_offset: 25.0,
-    });
-
-    comic_sans.chars.insert(47, Char {
-        code: 47,
-        width: 31.0,
-        height: 44.0,
-        x: 440.0,
-        x_advance: 16.0,
-        x_offset: -7.0,
-        y: 0.0,
-        y_offset: 2.0,
-    });
-
-    comic_sans.chars.insert(48, Char {
-        code: 48,
-        width: 35.0,
-        height: 42.0,
-        x: 68.0,
-        x_advance: 20.0,
-        x_offset: -8.0,
-        y: 135.0,
-        y_offset: 3.0,
-    });
-
-    comic_sans.chars.insert(49, Char {
-        code: 49,
-        width: 27.0,
-        height: 42.0,
-        x: 393.0,
-        x_advance: 14.0,
-        x_offset: -6.0,
-        y: 93.0,
-        y_offset: 3.0,
-    });
-
-    comic_sans.chars.insert(50, Char {
-        code: 50,
-        width: 32.0,
-        height: 41.0,
-        x: 463.0,
-        x_advance: 20.0,
-        x_offset: -6.0,
-        y: 135.0,
-        y_offset: 4.0,
-    });
-
-    comic_sans.chars.insert(51, Char {
-        code: 51,
-        width: 31.0,
-        height: 41.0,
-        x: 0.0,
-        x_advance: 20.0,
-        x_offset: -6.0,
-        y: 177.0,
-        y_offset: 4.0,
-    });
-
-    comic_sans.chars.insert(52, Char {
-        code: 52,
-        width: 35.0,
-        height: 42.0,
-        x: 420.0,
-        x_advance: 20.0,
-        x_offset: -8.0,
-        y: 93.0,
-        y_offset: 3.0,
-    });
-
-    comic_sans.chars.insert(53, Char {
-        code: 53,
-        width: 34.0,
-        height: 42.0,
-        x: 455.0,
-        x_advance: 20.0,
-        x_offset: -7.0,
-        y: 93.0,
-        y_offset: 3.0,
-    });
-
-    comic_sans.chars.insert(54, Char {
-        code: 54,
-        width: 33.0,
-        height: 43.0,
-        x: 296.0,
-        x_advance: 20.0,
-        x_offset: -7.0,
-        y: 50.0,
-        y_offset: 3.0,
-    });
-
-    comic_sans.chars.insert(55, Char {
-        code: 55,
-        width: 34.0,
-        height: 42.0,
-        x: 0.0,
-        x_advance: 20.0,
-        x_offset: -7.0,
-        y: 135.0,
-        y_offset: 4.0,
-    });
-
-    comic_sans.chars.insert(56, Char {
-        code: 56,
-        width: 33.0,
-        height: 41.0,
-        x: 31.0,
-        x_advance: 20.0,
-        x_offset: -7.0,
-        y: 177.0,
-        y_offset: 4.0,
-    });
-
-    comic_sans.chars.insert(57, Char {
-        code: 57,
-        width: 34.0,
-        height: 42.0,
-        x: 34.0,
-        x_advance: 20.0,
-        x_offset: -7.0,
-        y: 135.0,
-        y_offset: 4.0,
-    });
-
-    comic_sans.chars.insert(58, Char {
-        code: 58,
-        width: 21.0,
-        height: 33.0,
-        x: 63.0,
-        x_advance: 10.0,
-        x_offset: -6.0,
-        y: 218.0,
-        y_offset: 10.0,
-    });
-
-    comic_sans.chars.insert(59, Char {
-        code: 59,
-        width: 22.0,
-        height: 38.0,
-        x: 129.0,
-        x_advance: 10.0,
-        x_offset: -7.0,
-        y: 177.0,
-        y_offset: 10.0,
-    });
-
-    comic_sans.chars.insert(60, Char {
-        code: 60,
-        width: 26.0,
-        height: 30.0,
-        x: 112.0,
-        x_advance: 12.0,
-        x_offset: -8.0,
-        y: 218.0,
-        y_offset: 11.0,
-    });
-
-    comic_sans.chars.insert(61, Char {
-        code: 61,
-        width: 29.0,
-        height: 28.0,
-        x: 201.0,
-        x_advance: 16.0,
-        x_offset: -7.0,
-        y: 218.0,
-        y_offset: 12.0,
-    });
-
-    comic_sans.chars.insert(62, Char {
-        code: 62,
-        width: 28.0,
-        height: 32.0,
-        x: 84.0,
-        x_advance: 12.0,
-        x_offset: -8.0,
-        y: 218.0,
-        y_offset: 10.0,
-    });
-
-    comic_sans.chars.insert(63, Char {
-        code: 63,
-        width: 32.0,
-        height: 42.0,
-        x: 103.0,
-        x_advance: 17.0,
-        x_offset: -8.0,
-        y: 135.0,
-        y_offset: 4.0,
-    });
-
-    comic_sans.chars.insert(64, Char {
-        code: 64,
-        width: 43.0,
-        height: 45.0,
-        x: 282.0,
-        x_advance: 30.0,
-        x_offset: -7.0,
-        y: 0.0,
-        y_offset: 2.0,
-    });
-
-    comic_sans.chars.insert(65, Char {
-        code: 65,
-        width: 36.0,
-        height: 41.0,
-        x: 178.0,
-        x_advance: 23.0,
-        x_offset: -6.0,
-        y: 135.0,
-        y_offset: 4.0,
-    });
-
-    comic_sans.chars.insert(66, Char {
-        code: 66,
-        width: 33.0,
-        height: 42.0,
-        x: 434.0,
-        x_advance: 20.0,
-        x_offset: -6.0,
-        y: 50.0,
-        y_offset: 3.0,
-    });
-
-    comic_sans.chars.insert(67, Char {
-        code: 67,
-        width: 34.0,
-        height: 41.0,
-        x: 214.0,
-        x_advance: 19.0,
-        x_offset: -7.0,
-        y: 135.0,
-        y_offset: 4.0,
-    });
-
-    comic_sans.chars.insert(68, Char {
-        code: 68,
-        width: 36.0,
-        height: 43.0,
-        x: 471.0,
-        x_advance: 23.0,
-        x_offset: -6.0,
-        y: 0.0,
-        y_offset: 3.0,
-    });
-
-    comic_sans.chars.insert(69, Char {
-        code: 69,
-        width: 33.0,
-        height: 44.0,
-        x: 325.0,
-        x_advance: 20.0,
-        x_offset: -6.0,
-        y: 0.0,
-        y_offset: 2.0,
-    });
-
-    comic_sans.chars.insert(70, Char {
-        code: 70,
-        width: 33.0,
-        height: 43.0,
-        x: 0.0,
-        x_advance: 19.0,
-        x_offset: -6.0,
-        y: 50.0,
-        y_offset: 3.0,
-    });
-
-    comic_sans.chars.insert(71, Char {
-        code: 71,
-        width: 37.0,
-        height: 43.0,
-        x: 33.0,
-        x_advance: 22.0,
-        x_offset: -7.0,
-        y: 50.0,
-        y_offset: 3.0,
-    });
-
-    comic_sans.chars.insert(72, Char {
-        code: 72,
-        width: 37.0,
-        height: 43.0,
-        x: 70.0,
-        x_advance: 25.0,
-        x_offset: -6.0,
-        y: 50.0,
-        y_offset: 3.0,
-    });
-
-    comic_sans.chars.insert(73, Char {
-        code: 73,
-        width: 32.0,
-        height: 41.0,
-        x: 248.0,
-        x_advance: 17.0,
-        x_offset: -7.0,
-        y: 135.0,
-        y_offset: 4.0,
-    });
-
-    comic_sans.chars.insert(74, Char {
-        code: 74,
-        width: 36.0,
-        height: 42.0,
-        x: 467.0,
-        x_advance: 21.0,
-        x_offset: -7.0,
-        y: 50.0,
-        y_offset: 4.0,
-    });
-
-    comic_sans.chars.insert(75, Char {
-        code: 75,
-        width: 33.0,
-        height: 42.0,
-        x: 0.0,
-        x_advance: 20.0,
-        x_offset: -5.0,
-        y: 93.0,
-        y_offset: 4.0,
-    });
-
-    comic_sans.chars.insert(76, Char {
-        code: 76,
-        width: 33.0,
-        height: 43.0,
-        x: 107.0,
-        x_advance: 18.0,
-        x_offset: -7.0,
-        y: 50.0,
-        y_offset: 3.0,
-    });
-
-    comic_sans.chars.insert(77, Char {
-        code: 77,
-        width: 43.0,
-        height: 42.0,
-        x: 33.0,
-        x_advance: 28.0,
-        x_offset: -7.0,
-        y: 93.0,
-        y_offset: 4.0,
-    });
-
-    comic_sans.chars.insert(78, Char {
-        code: 78,
-        width: 40.0,
-        height: 43.0,
-        x: 140.0,
-        x_advance: 26.0,
-        x_offset: -7.0,
-        y: 50.0,
-        y_offset: 3.0,
-    });
-
-    comic_sans.chars.insert(79, Char {
-        code: 79,
-        width: 40.0,
-        height: 41.0,
-        x: 280.0,
-        x_advance: 26.0,
-        x_offset: -7.0,
-        y: 135.0,
-        y_offset: 4.0,
-    });
-
-    comic_sans.chars.insert(80, Char {
-        code: 80,
-        width: 31.0,
-        height: 42.0,
-        x: 76.0,
-        x_advance: 17.0,
-        x_offset: -7.0,
-        y: 93.0,
-        y_offset: 3.0,
-    });
-
-    comic_sans.chars.insert(81, Char {
-        code: 81,
-        width: 43.0,
-        height: 47.0,
-        x: 189.0,
-        x_advance: 28.0,
-        x_offset: -7.0,
-        y: 0.0,
-        y_offset: 4.0,
-    });
-
-    comic_sans.chars.insert(82, Char {
-        code: 82,
-        width: 35.0,
-        height: 42.0,
-        x: 107.0,
-        x_advance: 20.0,
-        x_offset: -7.0,
-        y: 93.0,
-        y_offset: 3.0,
-    });
-
-    comic_sans.chars.insert(83, Char {
-        code: 83,
-        width: 35.0,
-        height: 40.0,
-        x: 64.0,
-        x_advance: 22.0,
-        x_offset: -6.0,
-        y: 177.0,
-        y_offset: 5.0,
-    });
-
-    comic_sans.chars.insert(84, Char {
-        code: 84,
-        width: 38.0,
-        height: 41.0,
-        x: 320.0,
-        x_advance: 22.0,
-        x_offset: -7.0,
-        y: 135.0,
-        y_offset: 4.0,
-    });
-
-    comic_sans.chars.insert(85, Char {
-        code: 85,
-        width: 36.0,
-        height: 41.0,
-        x: 358.0,
-        x_advance: 24.0,
-        x_offset: -6.0,
-        y: 135.0,
-        y_offset: 4.0,
-    });
-
-    comic_sans.chars.insert(86, Char {
-        code: 86,
-        width: 35.0,
-        height: 42.0,
-        x: 142.0,
-        x_advance: 21.0,
-        x_offset: -6.0,
-        y: 93.0,
-        y_offset: 4.0,
-    });
-
-    comic_sans.chars.insert(87, Char {
-        code: 87,
-        width: 47.0,
-        height: 42.0,
-        x: 177.0,
-        x_advance: 33.0,
-        x_offset: -6.0,
-        y: 93.0,
-        y_offset: 4.0,
-    });
-
-    comic_sans.chars.insert(88, Char {
-        code: 88,
-        width: 37.0,
-        height: 42.0,
-        x: 224.0,
-        x_advance: 23.0,
-        x_offset: -7.0,
-        y: 93.0,
-        y_offset: 4.0,
-    });
-
-    comic_sans.chars.insert(89, Char {
-        code: 89,
-        width: 36.0,
-        height: 42.0,
-        x: 261.0,
-        x_advance: 20.0,
-        x_offset: -8.0,
-        y: 93.0,
-        y_offset: 4.0,
-    });
-
-    comic_sans.chars.insert(90, Char {
-        code: 90,
-        width: 37.0,
-        height: 41.0,
-        x: 394.0,
-        x_advance: 22.0,
-        x_offset: -7.0,
-        y: 135.0,
-        y_offset: 4.0,
-    });
-
-    comic_sans.chars.insert(91, Char {
-        code: 91,
-        width: 25.0,
-        height: 47.0,
-        x: 232.0,
-        x_advance: 12.0,
-        x_offset: -6.0,
-        y: 0.0,
-        y_offset: 4.0,
-    });
-
-    comic_sans.chars.insert(92, Char {
-        code: 92,
-        width: 30.0,
-        height: 43.0,
-        x: 329.0,
-        x_advance: 18.0,
-        x_offset: -6.0,
-        y: 50.0,
-        y_offset: 4.0,
-    });
-
-    comic_sans.chars.insert(93, Char {
-        code: 93,
-        width: 25.0,
-        height: 47.0,
-        x: 257.0,
-        x_advance: 12.0,
-        x_offset: -6.0,
-        y: 0.0,
-        y_offset: 4.0,
-    });
-
-    comic_sans.chars.insert(94, Char {
-        code: 94,
-        width: 29.0,
-        height: 25.0,
-        x: 297.0,
-        x_advance: 19.0,
-        x_offset: -5.0,
-        y: 218.0,
-        y_offset: 2.0,
-    });
-
-    comic_sans.chars.insert(95, Char {
-        code: 95,
-        width: 38.0,
-        height: 20.0,
-        x: 404.0,
-        x_advance: 20.0,
-        x_offset: -9.0,
-        y: 218.0,
-        y_offset: 30.0,
-    });
-
-    comic_sans.chars.insert(96, Char {
-        code: 96,
-        width: 24.0,
-        height: 24.0,
-        x: 326.0,
-        x_advance: 18.0,
-        x_offset: -6.0,
-        y: 218.0,
-        y_offset: 2.0,
-    });
-
-    comic_sans.chars.insert(97, Char {
-        code: 97,
-        width: 32.0,
-        height: 35.0,
-        x: 221.0,
-        x_advance: 16.0,
-        x_offset: -8.0,
-        y: 177.0,
-        y_offset: 11.0,
-    });
-
-    comic_sans.chars.insert(98, Char {
-        code: 98,
-        width: 32.0,
-        height: 42.0,
-        x: 297.0,
-        x_advance: 19.0,
-        x_offset: -6.0,
-        y: 93.0,
-        y_offset: 3.0,
-    });
-
-    comic_sans.chars.insert(99, Char {
-        code: 99,
-        width: 31.0,
-        height: 34.0,
-        x: 447.0,
-        x_advance: 16.0,
-        x_offset: -7.0,
-        y: 177.0,
-        y_offset: 11.0,
-    });
-
-    comic_sans.chars.insert(100, Char {
-        code: 100,
-        width: 33.0,
-        height: 42.0,
-        x: 329.0,
-        x_advance: 19.0,
-        x_offset: -7.0,
-        y: 93.0,
-        y_offset: 3.0,
-    });
-
-    comic_sans.chars.insert(101, Char {
-        code: 101,
-        width: 32.0,
-        height: 34.0,
-        x: 478.0,
-        x_advance: 18.0,
-        x_offset: -7.0,
-        y: 177.0,
-        y_offset: 11.0,
-    });
-
-    comic_sans.chars.insert(102, Char {
-        code: 102,
-        width: 30.0,
-        height: 44.0,
-        x: 358.0,
-        x_advance: 16.0,
-        x_offset: -7.0,
-        y: 0.0,
-        y_offset: 3.0,
-    });
-
-    comic_sans.chars.insert(103, Char {
-        code: 103,
-        width: 32.0,
-        height: 41.0,
-        x: 431.0,
-        x_advance: 17.0,
-        x_offset: -8.0,
-        y: 135.0,
-        y_offset: 12.0,
-    });
-
-    comic_sans.chars.insert(104, Char {
-        code: 104,
-        width: 31.0,
-        height: 43.0,
-        x: 180.0,
-        x_advance: 18.0,
-        x_offset: -6.0,
-        y: 50.0,
-        y_offset: 2.0,
-    });
-
-    comic_sans.chars.insert(105, Char {
-        code: 105,
-        width: 21.0,
-        height: 41.0,
-        x: 489.0,
-        x_advance: 9.0,
-        x_offset: -6.0,
-        y: 93.0,
-        y_offset: 4.0,
-    });
-
-    comic_sans.chars.insert(106, Char {
-        code: 106,
-        width: 28.0,
-        height: 50.0,
-        x: 0.0,
-        x_advance: 13.0,
-        x_offset: -9.0,
-        y: 0.0,
-        y_offset: 4.0,
-    });
-
-    comic_sans.chars.insert(107, Char {
-        code: 107,
-        width: 31.0,
-        height: 43.0,
-        x: 211.0,
-        x_advance: 17.0,
-        x_offset: -6.0,
-        y: 50.0,
-        y_offset: 2.0,
-    });
-
-    comic_sans.chars.insert(108, Char {
-        code: 108,
-        width: 21.0,
-        height: 43.0,
-        x: 242.0,
-        x_advance: 9.0,
-        x_offset: -6.0,
-        y: 50.0,
-        y_offset: 2.0,
-    });
-
-    comic_sans.chars.insert(109, Char {
-        code: 109,
-        width: 39.0,
-        height: 36.0,
-        x: 151.0,
-        x_advance: 25.0,
-        x_offset: -7.0,
-        y: 177.0,
-        y_offset: 10.0,
-    });
-
-    comic_sans.chars.insert(110, Char {
-        code: 110,
-        width: 31.0,
-        height: 36.0,
-        x: 190.0,
-        x_advance: 17.0,
-        x_offset: -7.0,
-        y: 177.0,
-        y_offset: 10.0,
-    });
-
-    comic_sans.chars.insert(111, Char {
-        code: 111,
-        width: 31.0,
-        height: 34.0,
-        x: 0.0,
-        x_advance: 17.0,
-        x_offset: -7.0,
-        y: 218.0,
-        y_offset: 11.0,
-    });
-
-    comic_sans.chars.insert(112, Char {
-        code: 112,
-        width: 31.0,
-        height: 44.0,
-        x: 388.0,
-        x_advance: 17.0,
-        x_offset: -7.0,
-        y: 0.0,
-        y_offset: 10.0,
-    });
-
-    comic_sans.chars.insert(113, Char {
-        code: 113,
-        width: 31.0,
-        height: 42.0,
-        x: 362.0,
-        x_advance: 17.0,
-        x_offset: -8.0,
-        y: 93.0,
-        y_offset: 11.0,
-    });
-
-    comic_sans.chars.insert(114, Char {
-        code: 114,
-        width: 29.0,
-        height: 35.0,
-        x: 253.0,
-        x_advance: 15.0,
-        x_offset: -6.0,
-        y: 177.0,
-        y_offset: 11.0,
-    });
-
-    comic_sans.chars.insert(115, Char {
-        code: 115,
-        width: 31.0,
-        height: 35.0,
-        x: 282.0,
-        x_advance: 16.0,
-        x_offset: -8.0,
-        y: 177.0,
-        y_offset: 10.0,
-    });
-
-    comic_sans.chars.insert(116, Char {
-        code: 116,
-        width: 30.0,
-        height: 39.0,
-        x: 99.0,
-        x_advance: 15.0,
-        x_offset: -7.0,
-        y: 177.0,
-        y_offset: 6.0,
-    });
-
-    comic_sans.chars.insert(117, Char {
-        code: 117,
-        width: 31.0,
-        height: 35.0,
-        x: 313.0,
-        x_advance: 17.0,
-        x_offset: -7.0,
-        y: 177.0,
-        y_offset: 11.0,
-    });
-
-    comic_sans.chars.insert(118, Char {
-        code: 118,
-        width: 32.0,
-        height: 34.0,
-        x: 31.0,
-        x_advance: 16.0,
-        x_offset: -8.0,
-        y: 218.0,
-        y_offset: 11.0,
-    });
-
-    comic_sans.chars.insert(119, Char {
-        code: 119,
-        width: 37.0,
-        height: 35.0,
-        x: 344.0,
-        x_advance: 22.0,
-        x_offset: -7.0,
-        y: 177.0,
-        y_offset: 11.0,
-    });
-
-    comic_sans.chars.insert(120, Char {
-        code: 120,
-        width: 34.0,
-        height: 35.0,
-        x: 381.0,
-        x_advance: 19.0,
-        x_offset: -8.0,
-        y: 177.0,
-        y_offset: 10.0,
-    });
-
-    comic_sans.chars.insert(121, Char {
-        code: 121,
-        width: 33.0,
-        height: 43.0,
-        x: 263.0,
-        x_advance: 17.0,
-        x_offset: -9.0,
-        y: 50.0,
-        y_offset: 11.0,
-    });
-
-    comic_sans.chars.insert(122, Char {
-        code: 122,
-        width: 32.0,
-        height: 35.0,
-        x: 415.0,
-        x_advance: 17.0,
-        x_offset: -7.0,
-        y: 177.0,
-        y_offset: 11.0,
-    });
-
-    comic_sans.chars.insert(123, Char {
-        code: 123,
-        width: 27.0,
-        height: 48.0,
-        x: 135.0,
-        x_advance: 12.0,
-        x_offset: -8.0,
-        y: 0.0,
-        y_offset: 2.0,
-    });
-
-    comic_sans.chars.insert(124, Char {
-        code: 124,
-        width: 20.0,
-        height: 49.0,
-        x: 115.0,
-        x_advance: 13.0,
-        x_offset: -3.0,
-        y: 0.0,
-        y_offset: 1.0,
-    });
-
-    comic_sans.chars.insert(125, Char {
-        code: 125,
-        width: 27.0,
-        height: 48.0,
-        x: 162.0,
-        x_advance: 12.0,
-        x_offset: -8.0,
-        y: 0.0,
-        y_offset: 2.0,
-    });
-
-    comic_sans.chars.insert(126, Char {
-        code: 126,
-        width: 33.0,
-        height: 24.0,
-        x: 350.0,
-        x_advance: 19.0,
-        x_offset: -7.0,
-        y: 218.0,
-        y_offset: 13.0,
-    });
-
-    comic_sans
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_line() {
+        let (tag, attributes) = parse_line("page id=0 file=\"comic sans.png\"");
+
+        assert_eq!(tag, "page");
+        assert_eq!(attributes.get("id"), Some(&"0"));
+        assert_eq!(attributes.get("file"), Some(&"comic sans.png"));
+
+        let (tag, attributes) = parse_line("char id=10      x=0    y=0    xoffset=-8   ");
+
+        assert_eq!(tag, "char");
+        assert_eq!(attributes.len(), 4);
+        assert_eq!(attributes.get("xoffset"), Some(&"-8"));
+
+        let (tag, attributes) = parse_line("");
+
+        assert_eq!(tag, "");
+        assert!(attributes.is_empty());
+    }
+
+    #[test]
+    fn test_from_fnt() {
+        let font = Font::from_fnt("comic-sans.fnt").unwrap();
+
+        assert_eq!(font.size, 32.0);
+        assert_eq!(font.line_height, 46.0);
+        assert_eq!(font.base, 36.0);
+        assert_eq!(font.scale_width, 512.0);
+        assert_eq!(font.scale_height, 256.0);
+        assert_eq!(font.chars.len(), 96);
+        assert_eq!(font.pages.len(), 1);
+        assert_eq!(font.pages[0].len(), 512 * 256 * 4);
+        assert_eq!(font.distance_field, DistanceField::Sdf);
+        assert_eq!(font.distance_range, 16.0);
+
+        let char = font.get_char('?');
+
+        assert_eq!(char.code, 63);
+        assert_eq!(char.page, 0);
+        assert_eq!(char.x, 103.0);
+        assert_eq!(char.y, 135.0);
+        assert_eq!(char.width, 32.0);
+        assert_eq!(char.height, 42.0);
+        assert_eq!(char.x_offset, -8.0);
+        assert_eq!(char.y_offset, 4.0);
+        assert_eq!(char.x_advance, 17.0);
+    }
+
+    #[test]
+    fn test_get_kerning() {
+        let mut font = Font::from_fnt("comic-sans.fnt").unwrap();
+
+        assert_eq!(font.get_kerning('A', 'V'), 0.0);
+
+        font.kernings.insert((65, 86), -2.0);
+        font.kernings.insert(('T' as u32, 'ā' as u32), -3.0);
+
+        assert_eq!(font.get_kerning('A', 'V'), -2.0);
+        assert_eq!(font.get_kerning('V', 'A'), 0.0);
+        assert_eq!(font.get_kerning('A', 'ñ'), 0.0);
+        assert_eq!(font.get_kerning('T', 'ā'), -3.0);
+        assert_eq!(font.get_kerning('T', 'a'), 0.0);
+    }
+
+    #[test]
+    fn test_get_char_fallback() {
+        let mut font = Font::from_fnt("comic-sans.fnt").unwrap();
+
+        // 'ā' is U+0101, it used to be truncated to U+0001
+        assert_eq!(font.get_char('ā').code, '?' as u32);
+        assert_eq!(font.get_char('ñ').code, '?' as u32);
+
+        let mut enye = font.get_char('n');
+        enye.code = 'ñ' as u32;
+        font.chars.insert(enye.code, enye);
+        font.fallback = '#' as u32;
+
+        assert_eq!(font.get_char('ñ').code, 'ñ' as u32);
+        assert_eq!(font.get_char('€').code, '#' as u32);
+
+        font.fallback = '€' as u32;
+
+        let char = font.get_char('€');
+
+        assert_eq!(char.code, '€' as u32);
+        assert_eq!(char.x_advance, 0.0);
+    }
+
+    #[test]
+    fn test_save_fnt() {
+        let mut font = Font::from_fnt("comic-sans.fnt").unwrap();
+        let directory = get_temp_directory("save_fnt");
+        let path = directory.join("comic-sans-copy.fnt");
+
+        font.kernings.insert(('A' as u32, 'V' as u32), -2.0);
+        fs::create_dir_all(&directory).unwrap();
+        font.save_fnt(&path).unwrap();
+
+        let copy = Font::from_fnt(&path).unwrap();
+
+        assert_eq!(copy.size, font.size);
+        assert_eq!(copy.line_height, font.line_height);
+        assert_eq!(copy.chars.len(), font.chars.len());
+        assert_eq!(copy.get_char('?').x, font.get_char('?').x);
+        assert_eq!(copy.get_kerning('A', 'V'), -2.0);
+        assert_eq!(copy.distance_range, font.distance_range);
+        assert_eq!(copy.pages, font.pages);
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_get_distance() {
+        let mut font = Font::from_fnt("comic-sans.fnt").unwrap();
+        let char = font.get_char('A');
+
+        // 2x2 page, every pixel has R=255, G=0, B=128 & A=0
+        font.scale_width = 2.0;
+        font.scale_height = 2.0;
+        font.pages = vec![[255, 0, 128, 0].repeat(4)];
+        font.distance_range = 4.0;
+
+        let point = Vec2::new(0.5, 0.5) - Vec2::new(char.x, char.y);
+
+        font.distance_field = DistanceField::Sdf;
+        assert_eq!(font.get_distance(&char, point), 2.0);
+
+        font.distance_field = DistanceField::Msdf;
+        assert!(font.get_distance(&char, point).abs() < 0.01);
+
+        // Far from the edge the true distance is used
+        font.distance_field = DistanceField::Mtsdf;
+        assert_eq!(font.get_distance(&char, point), 2.0);
+    }
+
+    #[test]
+    fn test_sample_edges() {
+        let mut font = Font::from_fnt("comic-sans.fnt").unwrap();
+
+        // 2x2 page, left column A=0 & right column A=255
+        font.scale_width = 2.0;
+        font.scale_height = 2.0;
+        font.pages = vec![[0, 0, 0, 0, 0, 0, 0, 255].repeat(2)];
+
+        assert_eq!(font.sample(0, Vec2::new(0.5, 0.5))[3], 0.5);
+        assert_eq!(font.sample(0, Vec2::new(1.0, 1.0))[3], 1.0);
+
+        // Past the edges the edge pixels are used, not extrapolated
+        assert_eq!(font.sample(0, Vec2::new(5.0, 7.0))[3], 1.0);
+        assert_eq!(font.sample(0, Vec2::new(-3.0, -1.0))[3], 0.0);
+    }
+
+    #[test]
+    fn test_from_fnt_small_page() {
+        let mut font = Font::from_fnt("comic-sans.fnt").unwrap();
+        let directory = get_temp_directory("small_page");
+        let path = directory.join("small.fnt");
+
+        font.scale_width = 1.0;
+        font.scale_height = 1.0;
+        font.pages = vec![vec![0; 4]];
+        fs::create_dir_all(&directory).unwrap();
+        font.save_fnt(&path).unwrap();
+
+        let result = Font::from_fnt(&path);
+
+        fs::remove_dir_all(&directory).unwrap();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_from_fnt_pages() {
+        let directory = get_temp_directory("fnt_pages");
+        let load = |pages: &str, count: usize| {
+            let source = format!(
+                "info size=32 padding=8,8,8,8\n{}\ncommon lineHeight=46 base=36 scaleW=2 scaleH=2 pages={}\nchar id=65 x=0 y=0 width=1 height=1 xoffset=0 yoffset=0 xadvance=1 page=0\n",
+                pages, count,
+            );
+
+            fs::write(directory.join("font.fnt"), source).unwrap();
+            Font::from_fnt(directory.join("font.fnt"))
+        };
+
+        fs::create_dir_all(&directory).unwrap();
+        save_png(directory.join("small.png"), &[0; 2 * 2 * 4], 2, 2).unwrap();
+        save_png(directory.join("large.png"), &[0; 4 * 4 * 4], 4, 4).unwrap();
+
+        // `common` after the pages, the first page still sets the size
+        let font = load("page id=1 file=\"small.png\"\npage id=0 file=\"small.png\"", 2);
+        let mismatch = load("page id=1 file=\"small.png\"\npage id=0 file=\"large.png\"", 2);
+
+        // Ids out of range are rejected before allocating anything
+        let huge_id = load("page id=4000000000 file=\"small.png\"", 1);
+        let repeated = load("page id=0 file=\"small.png\"\npage id=0 file=\"small.png\"", 2);
+        let missing = load("page id=0 file=\"small.png\"", 2);
+
+        fs::remove_dir_all(&directory).unwrap();
+
+        let font = font.unwrap();
+
+        assert_eq!(font.pages.len(), 2);
+        assert_eq!((font.scale_width, font.scale_height), (2.0, 2.0));
+        assert!(mismatch.is_err());
+        assert!(huge_id.is_err());
+        assert!(repeated.is_err());
+        assert!(missing.is_err());
+    }
 }
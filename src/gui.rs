@@ -96,6 +96,7 @@ impl Gui {
             ui.separator();
             ui.heading("Inspect");
             ui.label("Inspect options…");
+            ui.checkbox(&mut world.is_gradient_debug, "Show distance errors");
         });
     }
 
@@ -0,0 +1,157 @@
+use bevy_math::Vec2;
+use std::{error::Error, path::Path};
+
+use crate::color::Color;
+use crate::font::load_png;
+
+/// How the image repeats outside of its bounds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Wrap {
+    Tile,
+    /// Extend the edge pixels
+    Clamp,
+    /// Tile, flipping every other copy
+    Mirror,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Filter {
+    Nearest,
+    Bilinear,
+}
+
+/// RGBA image, pixels are stored row by row from the top-left with pre-multiplied alpha
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pixels: Vec<Color>,
+}
+
+impl Image {
+    pub fn from_png<P: AsRef<Path>>(path: P) -> Result<Image, Box<dyn Error>> {
+        let (data, width, height) = load_png(path)?;
+
+        Ok(Image::from_rgba(&data, width, height))
+    }
+
+    /// Creates an image from an 8-bit RGBA buffer (straight alpha)
+    pub fn from_rgba(data: &[u8], width: u32, height: u32) -> Image {
+        assert_eq!(data.len(), (width * height * 4) as usize, "buffer size doesn't match the image size");
+
+        Image {
+            width,
+            height,
+            pixels: data
+                .chunks_exact(4)
+                .map(|c| Color::new(c[0] as f32 / 255.0, c[1] as f32 / 255.0, c[2] as f32 / 255.0, c[3] as f32 / 255.0))
+                .collect(),
+        }
+    }
+
+    fn get_index(i: i64, size: u32, wrap: Wrap) -> usize {
+        let size = size as i64;
+
+        let i = match wrap {
+            Wrap::Tile => i.rem_euclid(size),
+            Wrap::Clamp => i.clamp(0, size - 1),
+            Wrap::Mirror => {
+                let i = i.rem_euclid(2 * size);
+                if i < size { i } else { 2 * size - 1 - i }
+            }
+        };
+
+        i as usize
+    }
+
+    pub fn get_pixel(&self, x: i64, y: i64, wrap: Wrap) -> Color {
+        if self.pixels.is_empty() {
+            return Color::new(0.0, 0.0, 0.0, 0.0);
+        }
+
+        let x = Image::get_index(x, self.width, wrap);
+        let y = Image::get_index(y, self.height, wrap);
+
+        self.pixels[y * self.width as usize + x].clone()
+    }
+
+    /// Samples the image at `uv`, where `(0, 0)` is the top-left corner and `(1, 1)` the bottom-right
+    pub fn sample(&self, uv: Vec2, wrap: Wrap, filter: Filter) -> Color {
+        // Pixel centers are at `.5`
+        let x = uv.x * self.width as f32 - 0.5;
+        let y = uv.y * self.height as f32 - 0.5;
+
+        match filter {
+            Filter::Nearest => self.get_pixel(x.round() as i64, y.round() as i64, wrap),
+            Filter::Bilinear => {
+                let (x0, y0) = (x.floor(), y.floor());
+                let (tx, ty) = (x - x0, y - y0);
+                let (x0, y0) = (x0 as i64, y0 as i64);
+
+                // Pre-multiplied colors can be interpolated directly
+                let top = self.get_pixel(x0, y0, wrap).lerp(&self.get_pixel(x0 + 1, y0, wrap), tx);
+                let bottom = self.get_pixel(x0, y0 + 1, wrap).lerp(&self.get_pixel(x0 + 1, y0 + 1, wrap), tx);
+
+                top.lerp(&bottom, ty)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2x2: red, green / blue, transparent white
+    fn get_image() -> Image {
+        Image::from_rgba(&[
+            255, 0, 0, 255,   0, 255, 0, 255,
+            0, 0, 255, 255,   255, 255, 255, 0,
+        ], 2, 2)
+    }
+
+    fn assert_color(color: Color, expected: (f32, f32, f32, f32)) {
+        let (r, g, b, a) = expected;
+
+        assert!(
+            (color.r - r).abs() < 0.001 && (color.g - g).abs() < 0.001 && (color.b - b).abs() < 0.001 && (color.a - a).abs() < 0.001,
+            "{:?} != {:?}", color, expected
+        );
+    }
+
+    #[test]
+    fn test_premultiplied() {
+        let image = Image::from_rgba(&[255, 255, 255, 127], 1, 1);
+
+        assert_color(image.get_pixel(0, 0, Wrap::Clamp), (0.498, 0.498, 0.498, 0.498));
+    }
+
+    #[test]
+    fn test_wrap() {
+        let image = get_image();
+
+        assert_color(image.get_pixel(2, 0, Wrap::Tile), (1.0, 0.0, 0.0, 1.0));
+        assert_color(image.get_pixel(-1, 0, Wrap::Tile), (0.0, 1.0, 0.0, 1.0));
+        assert_color(image.get_pixel(5, -3, Wrap::Clamp), (0.0, 1.0, 0.0, 1.0));
+        assert_color(image.get_pixel(2, 0, Wrap::Mirror), (0.0, 1.0, 0.0, 1.0));
+        assert_color(image.get_pixel(-1, 0, Wrap::Mirror), (1.0, 0.0, 0.0, 1.0));
+        assert_color(image.get_pixel(4, 0, Wrap::Mirror), (1.0, 0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn test_sample() {
+        let image = get_image();
+
+        assert_color(image.sample(Vec2::new(0.25, 0.25), Wrap::Clamp, Filter::Nearest), (1.0, 0.0, 0.0, 1.0));
+        assert_color(image.sample(Vec2::new(0.7, 0.3), Wrap::Clamp, Filter::Nearest), (0.0, 1.0, 0.0, 1.0));
+        assert_color(image.sample(Vec2::new(0.25, 0.25), Wrap::Clamp, Filter::Bilinear), (1.0, 0.0, 0.0, 1.0));
+
+        // Halfway between red & green
+        assert_color(image.sample(Vec2::new(0.5, 0.25), Wrap::Clamp, Filter::Bilinear), (0.5, 0.5, 0.0, 1.0));
+
+        // Center, the transparent pixel doesn't bleed its color
+        assert_color(image.sample(Vec2::new(0.5, 0.5), Wrap::Clamp, Filter::Bilinear), (0.25, 0.25, 0.25, 0.75));
+
+        // Tiling wraps around the edges
+        assert_color(image.sample(Vec2::new(0.0, 0.25), Wrap::Tile, Filter::Bilinear), (0.5, 0.5, 0.0, 1.0));
+    }
+}
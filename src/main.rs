@@ -9,23 +9,29 @@ use winit_input_helper::WinitInputHelper;
 
 mod gui;
 
-use bevy_math::{Vec2};
+use bevy_math::{Mat3, Vec2};
 use rayon::prelude::*;
 use std::{error::Error, sync::Arc, time::Instant};
 
 // Project modules
+mod baker;
 mod color;
 mod distortion;
 mod font;
+mod image;
+mod modifier;
+mod noise;
+mod outline;
 mod utils;
 mod sdf;
 mod transform;
 
+use baker::*;
 use color::*;
 use distortion::*;
 use font::*;
 use utils::*;
-use sdf::{*, color::{Border, BorderPosition, Fill, LayerColor, SDFColor}};
+use sdf::{*, color::{Border, BorderPosition, Fill, LayerColor, SDFColor, ShadingContext}};
 use transform::*;
 
 // Main
@@ -37,27 +43,18 @@ struct Layer {
     shape: usize,
 }
 
-fn get_debug_transform(mut parent_id: usize, arena: &Vec<Object>) -> Transform {
-    let mut transforms: Vec<Transform> = vec![];
-    let mut debug_transform = Transform::new();
+/// Maps world points to the space of the selected object's parent, where its own transform applies
+fn get_debug_matrix(id: usize, arena: &Vec<Object>) -> Mat3 {
+    let mut matrix = Mat3::identity();
+    let mut parent_id = arena[id].parent_id;
 
-    loop {
-        transforms.push(arena[parent_id].transform);
-
-        match arena[parent_id].parent_id {
-            Some(id) => parent_id = id,
-            None => break,
-        }
-    }
-
-    transforms = transforms.into_iter().rev().collect(); // Reverse transforms
-    transforms.pop(); // Remove transform corresponding to the selected object
-
-    for transform in transforms {
-        debug_transform.apply(transform);
+    // Parent matrices go first (to the left)
+    while let Some(id) = parent_id {
+        matrix = arena[id].transform.to_matrix() * matrix;
+        parent_id = arena[id].parent_id;
     }
 
-    debug_transform
+    matrix.inverse()
 }
 
 struct World<'a> {
@@ -66,20 +63,24 @@ struct World<'a> {
     objects: Vec<Object<'a>>,
     selected_id: usize,
     is_debug: bool,
+    is_gradient_debug: bool,
     is_initialized: bool,
-    debug_transform: Transform,
+    debug_matrix: Mat3,
+    time: f32,
 }
 
 impl<'a> World<'a> {
     fn new() -> World<'a> {
         World {
-            font: Arc::new(get_comic_sans()),
+            font: Arc::new(Font::from_fnt("comic-sans.fnt").expect("Unable to load comic-sans.fnt")),
             layers: vec![],
             objects: vec![],
             selected_id: 0,
             is_debug: true,
+            is_gradient_debug: false,
             is_initialized: false,
-            debug_transform: Transform::new(),
+            debug_matrix: Mat3::identity(),
+            time: 0.0,
         }
     }
 
@@ -93,13 +94,9 @@ impl<'a> World<'a> {
         self.objects = vec![
             // 0
             Object {
-                transform: Transform {
-                    x: 0.0,
-                    y: 0.0,
-                    rotation: 0.0,
-                    scale: 1.0,
-                },
+                transform: Transform::new(),
                 distortion: Vec::new(),
+                modifier: Vec::new(),
                 parent_id: None,
                 sdf: Box::new(operator::OpSmoothUnion {
                     sdf_1: 1,
@@ -109,13 +106,9 @@ impl<'a> World<'a> {
             },
             // 1
             Object {
-                transform: Transform {
-                    x: 0.0,
-                    y: 0.0,
-                    rotation: 0.0,
-                    scale: 1.0,
-                },
+                transform: Transform::new(),
                 distortion: Vec::new(),
+                modifier: Vec::new(),
                 parent_id: Some(0),
                 sdf: Box::new(primitive::Circle {
                     radius: 50.0,
@@ -123,13 +116,9 @@ impl<'a> World<'a> {
             },
             // 2
             Object {
-                transform: Transform {
-                    x: 0.0,
-                    y: 0.0,
-                    rotation: 0.0,
-                    scale: 1.0,
-                },
+                transform: Transform::new(),
                 distortion: Vec::new(),
+                modifier: Vec::new(),
                 parent_id: Some(0),
                 sdf: Box::new(primitive::Square {
                     size: Vec2::new(100.0, 10.0)
@@ -138,12 +127,12 @@ impl<'a> World<'a> {
             // 3
             Object {
                 transform: Transform {
-                    x: 0.0,
                     y: (HEIGHT / 6) as f32,
                     rotation: 15.0,
-                    scale: 1.0,
+                    ..Transform::new()
                 },
                 distortion: Vec::new(),
+                modifier: Vec::new(),
                 parent_id: None,
                 sdf: Box::new(primitive::Square {
                     size: Vec2::new(10.0, 100.0)
@@ -152,10 +141,8 @@ impl<'a> World<'a> {
             // 4
             Object {
                 transform: Transform {
-                    x: 0.0,
                     y: -100.0,
-                    rotation: 0.0,
-                    scale: 1.0,
+                    ..Transform::new()
                 },
                 distortion: vec![
                     Box::new(Wave {
@@ -168,6 +155,7 @@ impl<'a> World<'a> {
                         time: 1.0,
                     })
                 ],
+                modifier: Vec::new(),
                 parent_id: None,
                 sdf: Box::new(primitive::Circle {
                     radius: 100.0,
@@ -178,8 +166,8 @@ impl<'a> World<'a> {
                 transform: Transform {
                     x: -150.0,
                     y: -65.0,
-                    rotation: 0.0,
-                    scale: 1.2,
+                    scale: Vec2::new(1.2, 1.2),
+                    ..Transform::new()
                 },
                 distortion: vec![
                     Box::new(Wave {
@@ -192,6 +180,7 @@ impl<'a> World<'a> {
                         time: 1.0,
                     })
                 ],
+                modifier: Vec::new(),
                 parent_id: None,
                 sdf: Box::new(primitive::Text::new(String::from("Hello world! :-)"), 32.0, Arc::clone(&self.font)))
                 // sdf: Box::new(primitive::Circle { radius: 10.0 })
@@ -201,10 +190,10 @@ impl<'a> World<'a> {
                 transform: Transform {
                     x: -100.0,
                     y: 100.0,
-                    rotation: 0.0,
-                    scale: 1.0,
+                    ..Transform::new()
                 },
                 distortion: Vec::new(),
+                modifier: Vec::new(),
                 parent_id: None,
                 sdf: Box::new(primitive::Circle {
                     radius: 3.0,
@@ -279,10 +268,10 @@ impl<'a> World<'a> {
 
                     // Draw layer top to bottom
                     for layer in &self.layers {
-                        let distance = self.objects[layer.shape].get_distance(&self.objects, point);
+                        let context = ShadingContext::new(&self.objects, layer.shape, point, self.time);
 
                         // Mix front color with layer color
-                        let back_color = layer.color.get_color(distance);
+                        let back_color = layer.color.get_color(&context);
                         color = back_color.mix(&color);
 
                         // Alpha check to skip below layers
@@ -296,7 +285,7 @@ impl<'a> World<'a> {
 
                     // Draw debug elements
                     if self.is_debug {
-                        let point = self.debug_transform.map(point.clone());
+                        let point = self.debug_matrix.transform_point2(point);
                         let distance = self.objects[self.selected_id].get_distance(&self.objects, point);
                         let border_width = 2.0;
                         let alpha = smoothstep(0.0, border_width, distance) - smoothstep(border_width, border_width * 2.0, distance);
@@ -305,6 +294,22 @@ impl<'a> World<'a> {
                         color = color.mix(&debug_color);
                     }
 
+                    // Highlight where the selected object isn't a true distance field (|gradient| != 1.0):
+                    // red where the distance grows too fast (overshoots), blue where it grows too slow
+                    if self.is_gradient_debug {
+                        let point = self.debug_matrix.transform_point2(point);
+                        let gradient = get_gradient(&self.objects[self.selected_id], &self.objects, point, 0.5).length();
+                        let error = gradient - 1.0;
+                        let alpha = smoothstep(0.05, 0.5, error.abs()) * 0.75;
+                        let debug_color = if error > 0.0 {
+                            Color::new(1.0, 0.0, 0.0, alpha)
+                        } else {
+                            Color::new(0.0, 0.4, 1.0, alpha)
+                        };
+
+                        color = color.mix(&debug_color);
+                    }
+
                     pixel.copy_from_slice(&color.to_array());
                 }
             });
@@ -340,18 +345,21 @@ impl<'a> World<'a> {
         //     }
         // });
 
+        self.time = time;
+
         // Selected parents transforms tree
-        self.debug_transform = get_debug_transform(self.selected_id, &self.objects);
+        self.debug_matrix = get_debug_matrix(self.selected_id, &self.objects);
 
         // Update first object
         self.objects[0] = Object {
             transform: Transform {
                 rotation: time * 5.0,
-                scale: 1.0 + ((time * 2.0).sin() * 0.25),
+                scale: Vec2::splat(1.0 + ((time * 2.0).sin() * 0.25)),
                 ..self.objects[0].transform
             },
             parent_id: self.objects[0].parent_id,
             distortion: Vec::new(),
+            modifier: Vec::new(),
             sdf: Box::new(operator::OpSmoothUnion {
                 sdf_1: 1,
                 sdf_2: 2,
@@ -384,6 +392,14 @@ impl<'a> World<'a> {
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
+    // `sdf_2d bake <font.ttf> <font.fnt>` generates an SDF font atlas and exits
+    let args: Vec<String> = std::env::args().collect();
+
+    if args.len() == 4 && args[1] == "bake" {
+        FontBaker::new().bake(&args[2])?.save_fnt(&args[3])?;
+        return Ok(());
+    }
+
     let start_time = Instant::now();
     let mut world = World::new();
     let mut frame = 0;
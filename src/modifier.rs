@@ -0,0 +1,67 @@
+/// Post-processes the distance returned by an object's SDF, the counterpart of `Distorsion` which maps the point
+pub trait Modifier {
+    fn apply(&self, distance: f32) -> f32;
+}
+
+/// Keeps a `thickness` wide outline centered on the shape edge.
+/// Stacking several of them splits each ring in two, making concentric rings.
+pub struct Onion {
+    pub thickness: f32,
+}
+
+impl Modifier for Onion {
+    fn apply(&self, distance: f32) -> f32 {
+        distance.abs() - self.thickness / 2.0
+    }
+}
+
+/// Inflates the shape by `radius`, rounding the convex corners
+pub struct Round {
+    pub radius: f32,
+}
+
+impl Modifier for Round {
+    fn apply(&self, distance: f32) -> f32 {
+        distance - self.radius
+    }
+}
+
+/// Multiplies the distance by `factor`, values over `1.0` make the anti-aliased edge and
+/// the gradients based on distance tighter. The edge stays in place.
+pub struct Sharpen {
+    pub factor: f32,
+}
+
+impl Modifier for Sharpen {
+    fn apply(&self, distance: f32) -> f32 {
+        distance * self.factor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_onion() {
+        let onion = Onion { thickness: 2.0 };
+
+        assert_eq!(onion.apply(0.0), -1.0);
+        assert_eq!(onion.apply(-3.0), 2.0);
+        assert_eq!(onion.apply(3.0), 2.0);
+
+        // Twice makes two rings, centered at -1.0 & 1.0
+        let rings = |distance: f32| Onion { thickness: 0.5 }.apply(onion.apply(distance));
+
+        assert_eq!(rings(0.0), 0.75);
+        assert_eq!(rings(1.0), -0.25);
+        assert_eq!(rings(-1.0), -0.25);
+    }
+
+    #[test]
+    fn test_round_and_sharpen() {
+        assert_eq!(Round { radius: 2.0 }.apply(1.0), -1.0);
+        assert_eq!(Sharpen { factor: 2.0 }.apply(-1.5), -3.0);
+        assert_eq!(Sharpen { factor: 2.0 }.apply(0.0), 0.0);
+    }
+}
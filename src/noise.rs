@@ -0,0 +1,223 @@
+use bevy_math::Vec2;
+
+// Noises in the [-1.0, 1.0] range, the same `seed` always gives the same noise
+
+const GRADIENTS: [(f32, f32); 8] = [
+    (1.0, 0.0),
+    (-1.0, 0.0),
+    (0.0, 1.0),
+    (0.0, -1.0),
+    (0.707_106_77, 0.707_106_77),
+    (-0.707_106_77, 0.707_106_77),
+    (0.707_106_77, -0.707_106_77),
+    (-0.707_106_77, -0.707_106_77),
+];
+
+// Upper bounds of the gradient length of each noise, distortions use them to know how much they stretch
+// the space. `fade` slope is 1.875 at most.
+
+/// Lattice values are in [-1.0, 1.0], each axis slope is the fade slope times 2.0 at most
+pub const VALUE_GRADIENT_BOUND: f32 = 3.75 * std::f32::consts::SQRT_2;
+/// The blend of the unit lattice gradients plus the fade slope times the difference between the
+/// corners dot products (at most `√2` each, at the cell center), scaled by `√2` like `perlin`
+pub const PERLIN_GRADIENT_BOUND: f32 = (1.0 + 1.875 * 2.0) * std::f32::consts::SQRT_2;
+/// Each of the 3 corners slope is `(3/7)³` at most, scaled by 99.0 like `simplex`
+pub const SIMPLEX_GRADIENT_BOUND: f32 = 3.0 * 0.078_717_2 * 99.0;
+
+/// Integer hash of a lattice point
+fn hash(x: i32, y: i32, seed: u32) -> u32 {
+    let mut h = seed ^ (x as u32).wrapping_mul(0x27d4_eb2d) ^ (y as u32).wrapping_mul(0x1656_67b1);
+
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x2c1b_3c6d);
+    h ^= h >> 12;
+    h = h.wrapping_mul(0x297a_2d39);
+    h ^ (h >> 15)
+}
+
+/// Dot product of the lattice point gradient and the offset from it
+fn gradient_dot(x: i32, y: i32, seed: u32, offset: Vec2) -> f32 {
+    let (gx, gy) = GRADIENTS[(hash(x, y, seed) & 7) as usize];
+    gx * offset.x + gy * offset.y
+}
+
+/// Quintic fade, continuous second derivative
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+/// Value noise, interpolates random values at the lattice points. Blockier than the gradient noises.
+pub fn value(point: Vec2, seed: u32) -> f32 {
+    let (x, y) = (point.x.floor(), point.y.floor());
+    let (i, j) = (x as i32, y as i32);
+    let (u, v) = (fade(point.x - x), fade(point.y - y));
+    let lattice = |x: i32, y: i32| hash(x, y, seed) as f32 / u32::MAX as f32 * 2.0 - 1.0;
+
+    let nx0 = lattice(i, j) + (lattice(i + 1, j) - lattice(i, j)) * u;
+    let nx1 = lattice(i, j + 1) + (lattice(i + 1, j + 1) - lattice(i, j + 1)) * u;
+
+    nx0 + (nx1 - nx0) * v
+}
+
+pub fn perlin(point: Vec2, seed: u32) -> f32 {
+    let (x, y) = (point.x.floor(), point.y.floor());
+    let (i, j) = (x as i32, y as i32);
+    let f = point - Vec2::new(x, y);
+
+    let (u, v) = (fade(f.x), fade(f.y));
+
+    let n00 = gradient_dot(i, j, seed, f);
+    let n10 = gradient_dot(i + 1, j, seed, f - Vec2::new(1.0, 0.0));
+    let n01 = gradient_dot(i, j + 1, seed, f - Vec2::new(0.0, 1.0));
+    let n11 = gradient_dot(i + 1, j + 1, seed, f - Vec2::new(1.0, 1.0));
+
+    let nx0 = n00 + (n10 - n00) * u;
+    let nx1 = n01 + (n11 - n01) * u;
+
+    ((nx0 + (nx1 - nx0) * v) * std::f32::consts::SQRT_2).clamp(-1.0, 1.0)
+}
+
+/// Simplex noise (Stefan Gustavson, "Simplex noise demystified"), fewer directional artifacts than `perlin`
+pub fn simplex(point: Vec2, seed: u32) -> f32 {
+    let f2 = 0.5 * (3.0f32.sqrt() - 1.0);
+    let g2 = (3.0 - 3.0f32.sqrt()) / 6.0;
+
+    // Skew to the simplex grid
+    let s = (point.x + point.y) * f2;
+    let (i, j) = ((point.x + s).floor(), (point.y + s).floor());
+    let t = (i + j) * g2;
+    let p0 = point - Vec2::new(i - t, j - t);
+    let (i1, j1) = if p0.x > p0.y { (1, 0) } else { (0, 1) };
+    let p1 = p0 - Vec2::new(i1 as f32, j1 as f32) + Vec2::splat(g2);
+    let p2 = p0 - Vec2::splat(1.0 - 2.0 * g2);
+    let (i, j) = (i as i32, j as i32);
+
+    let corner = |x: i32, y: i32, offset: Vec2| {
+        let t = 0.5 - offset.length_squared();
+
+        if t < 0.0 { 0.0 } else { t.powi(4) * gradient_dot(x, y, seed, offset) }
+    };
+
+    let n = corner(i, j, p0) + corner(i + i1, j + j1, p1) + corner(i + 1, j + 1, p2);
+
+    (n * 99.0).clamp(-1.0, 1.0)
+}
+
+/// Fractal sum of `octaves` layers of `noise`, each one with double frequency & half amplitude.
+/// The result is normalized back to [-1.0, 1.0].
+pub fn fbm(noise: fn(Vec2, u32) -> f32, point: Vec2, seed: u32, octaves: u32) -> f32 {
+    let mut value = 0.0;
+    let mut amplitude = 1.0;
+    let mut total = 0.0;
+    let mut point = point;
+
+    for octave in 0..octaves.max(1) {
+        value += noise(point, seed.wrapping_add(octave)) * amplitude;
+        total += amplitude;
+        amplitude *= 0.5;
+        point *= 2.0;
+    }
+
+    value / total
+}
+
+/// How much `fbm` & `turbulence` can multiply the gradient of the noise: each octave
+/// doubles the frequency & halves the amplitude, so all of them add the same slope
+pub fn get_fractal_gradient_scale(octaves: u32) -> f32 {
+    let octaves = octaves.max(1);
+
+    octaves as f32 / (2.0 * (1.0 - 0.5f32.powi(octaves as i32)))
+}
+
+/// Like `fbm` with the absolute value of each octave, billowy noise in the [0.0, 1.0] range
+pub fn turbulence(noise: fn(Vec2, u32) -> f32, point: Vec2, seed: u32, octaves: u32) -> f32 {
+    let mut value = 0.0;
+    let mut amplitude = 1.0;
+    let mut total = 0.0;
+    let mut point = point;
+
+    for octave in 0..octaves.max(1) {
+        value += noise(point, seed.wrapping_add(octave)).abs() * amplitude;
+        total += amplitude;
+        amplitude *= 0.5;
+        point *= 2.0;
+    }
+
+    value / total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_samples(noise: fn(Vec2, u32) -> f32, seed: u32) -> Vec<f32> {
+        (0..10000).map(|i| noise(Vec2::new((i % 100) as f32 * 0.173 - 7.0, (i / 100) as f32 * 0.291 - 11.0), seed)).collect()
+    }
+
+    #[test]
+    fn test_noise_range() {
+        for &noise in &[value as fn(Vec2, u32) -> f32, perlin, simplex] {
+            let samples = get_samples(noise, 7);
+            let min = samples.iter().cloned().fold(f32::MAX, f32::min);
+            let max = samples.iter().cloned().fold(f32::MIN, f32::max);
+            let mean = samples.iter().sum::<f32>() / samples.len() as f32;
+
+            assert!(min >= -1.0 && max <= 1.0);
+            // Uses most of the range and it's centered
+            assert!(min < -0.5 && max > 0.5, "{} {}", min, max);
+            assert!(mean.abs() < 0.05);
+        }
+    }
+
+    #[test]
+    fn test_gradient_bounds() {
+        let bounds = [
+            (value as fn(Vec2, u32) -> f32, VALUE_GRADIENT_BOUND),
+            (perlin, PERLIN_GRADIENT_BOUND),
+            (simplex, SIMPLEX_GRADIENT_BOUND),
+        ];
+        let h = 0.001;
+
+        for &(noise, bound) in &bounds {
+            for seed in 0..2 {
+                for i in 0..10000 {
+                    let point = Vec2::new((i % 100) as f32 * 0.0173 - 0.3, (i / 100) as f32 * 0.0291 - 1.1);
+                    let gradient = Vec2::new(
+                        noise(point + Vec2::new(h, 0.0), seed) - noise(point - Vec2::new(h, 0.0), seed),
+                        noise(point + Vec2::new(0.0, h), seed) - noise(point - Vec2::new(0.0, h), seed),
+                    ) / (2.0 * h);
+
+                    assert!(gradient.length() <= bound, "{:?} {} {}", point, gradient.length(), bound);
+                }
+            }
+        }
+
+        assert_eq!(get_fractal_gradient_scale(1), 1.0);
+        assert_eq!(get_fractal_gradient_scale(2), 2.0 / 1.5);
+    }
+
+    #[test]
+    fn test_noise_seed() {
+        assert_eq!(get_samples(perlin, 1), get_samples(perlin, 1));
+        assert_ne!(get_samples(perlin, 1), get_samples(perlin, 2));
+        assert_ne!(get_samples(simplex, 1), get_samples(simplex, 2));
+        assert_ne!(get_samples(value, 1), get_samples(value, 2));
+
+        // Zero at the lattice points
+        assert_eq!(perlin(Vec2::new(3.0, -2.0), 5), 0.0);
+    }
+
+    #[test]
+    fn test_fractal_noise() {
+        for i in 0..1000 {
+            let point = Vec2::new(i as f32 * 0.37, i as f32 * -0.11);
+            let value = fbm(simplex, point, 3, 5);
+            let turbulence = turbulence(perlin, point, 3, 5);
+
+            assert!((-1.0..=1.0).contains(&value));
+            assert!((0.0..=1.0).contains(&turbulence));
+        }
+
+        assert_eq!(fbm(perlin, Vec2::new(0.3, 0.6), 1, 1), perlin(Vec2::new(0.3, 0.6), 1));
+    }
+}
@@ -0,0 +1,571 @@
+use bevy_math::Vec2;
+use ttf_parser as ttf;
+
+/// Outline segment, curves are quadratic & cubic Bézier curves
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Segment {
+    Line(Vec2, Vec2),
+    Quad(Vec2, Vec2, Vec2),
+    Cubic(Vec2, Vec2, Vec2, Vec2),
+}
+
+/// Real roots of `a·t² + b·t + c = 0`, returns how many were written in `roots`
+fn solve_quadratic(a: f64, b: f64, c: f64, roots: &mut [f64; 3]) -> usize {
+    if a.abs() <= 1e-12 * (b.abs() + c.abs()) || a == 0.0 {
+        if b == 0.0 {
+            return 0;
+        }
+
+        roots[0] = -c / b;
+        return 1;
+    }
+
+    let discriminant = b * b - 4.0 * a * c;
+
+    if discriminant < 0.0 {
+        return 0;
+    }
+
+    let sqrt = discriminant.sqrt();
+
+    roots[0] = (-b - sqrt) / (2.0 * a);
+    roots[1] = (-b + sqrt) / (2.0 * a);
+    2
+}
+
+/// Real roots of `a·t³ + b·t² + c·t + d = 0`, returns how many were written in `roots`
+fn solve_cubic(a: f64, b: f64, c: f64, d: f64, roots: &mut [f64; 3]) -> usize {
+    if a.abs() <= 1e-12 * (b.abs() + c.abs() + d.abs()) || a == 0.0 {
+        return solve_quadratic(b, c, d, roots);
+    }
+
+    // Depressed cubic `x³ + p·x + q = 0`, where `t = x - b / 3`
+    let (b, c, d) = (b / a, c / a, d / a);
+    let offset = b / 3.0;
+    let p = c - b * b / 3.0;
+    let q = 2.0 * b * b * b / 27.0 - b * c / 3.0 + d;
+    let discriminant = q * q / 4.0 + p * p * p / 27.0;
+
+    let count = if discriminant > 0.0 {
+        // One real root
+        let sqrt = discriminant.sqrt();
+        roots[0] = (-q / 2.0 + sqrt).cbrt() + (-q / 2.0 - sqrt).cbrt();
+        1
+    } else if p == 0.0 {
+        roots[0] = 0.0;
+        1
+    } else {
+        // Three real roots, trigonometric method
+        let r = (-p / 3.0).sqrt();
+        let phi = ((3.0 * q) / (2.0 * p) * (-3.0 / p).sqrt()).clamp(-1.0, 1.0).acos() / 3.0;
+
+        for (k, root) in roots.iter_mut().enumerate() {
+            *root = 2.0 * r * (phi - 2.0 * std::f64::consts::PI * k as f64 / 3.0).cos();
+        }
+
+        3
+    };
+
+    for root in roots.iter_mut().take(count) {
+        *root -= offset;
+    }
+
+    count
+}
+
+/// Real roots of the polynomial in `[min, max]` in ascending order, `coefficients[i]` multiplies `tⁱ` (degree 5 at most).
+/// Between two roots of the derivative the polynomial is monotonic, so each of those intervals has one
+/// root at most, found with bisection (sped up with Newton steps that stay inside the interval).
+fn solve_polynomial(coefficients: &[f64], min: f64, max: f64, roots: &mut [f64; 5]) -> usize {
+    let mut degree = coefficients.len() - 1;
+
+    while degree > 0 && coefficients[degree] == 0.0 {
+        degree -= 1;
+    }
+
+    let coefficients = &coefficients[..=degree];
+    let mut derivative = [0.0; 5];
+
+    for i in 1..=degree {
+        derivative[i - 1] = coefficients[i] * i as f64;
+    }
+
+    let derivative = &derivative[..degree];
+    let evaluate = |coefficients: &[f64], t: f64| coefficients.iter().rev().fold(0.0, |value, &c| value * t + c);
+    let mut count = 0;
+    let mut push = |t: f64, roots: &mut [f64; 5]| {
+        if count == 0 || roots[count - 1] != t {
+            roots[count] = t;
+            count += 1;
+        }
+    };
+
+    if degree == 0 {
+        return 0;
+    }
+
+    // Interval limits: `min`, the derivative roots & `max`
+    let mut limits = [0.0; 6];
+    let limit_count = if degree > 1 {
+        let mut critical = [0.0; 5];
+        let critical_count = solve_polynomial(derivative, min, max, &mut critical);
+
+        limits[1..=critical_count].copy_from_slice(&critical[..critical_count]);
+        critical_count + 2
+    } else {
+        2
+    };
+
+    limits[0] = min;
+    limits[limit_count - 1] = max;
+
+    for interval in limits[..limit_count].windows(2) {
+        let (mut a, mut b) = (interval[0], interval[1]);
+        let (value_a, value_b) = (evaluate(coefficients, a), evaluate(coefficients, b));
+
+        if value_a == 0.0 {
+            push(a, roots);
+            continue;
+        }
+
+        if value_b == 0.0 || value_a.signum() == value_b.signum() {
+            continue;
+        }
+
+        let mut t = (a + b) * 0.5;
+
+        for _ in 0..100 {
+            let value = evaluate(coefficients, t);
+
+            if value == 0.0 || b - a < 1e-12 {
+                break;
+            }
+
+            if value.signum() == value_a.signum() { a = t } else { b = t }
+
+            let slope = evaluate(derivative, t);
+            let newton = if slope != 0.0 { t - value / slope } else { f64::NAN };
+
+            t = if newton > a && newton < b { newton } else { (a + b) * 0.5 };
+        }
+
+        push(t, roots);
+    }
+
+    if evaluate(coefficients, max) == 0.0 {
+        push(max, roots);
+    }
+
+    count
+}
+
+impl Segment {
+    pub fn get_start(&self) -> Vec2 {
+        match *self {
+            Segment::Line(a, _) | Segment::Quad(a, _, _) | Segment::Cubic(a, _, _, _) => a,
+        }
+    }
+
+    pub fn get_end(&self) -> Vec2 {
+        match *self {
+            Segment::Line(_, b) | Segment::Quad(_, _, b) | Segment::Cubic(_, _, _, b) => b,
+        }
+    }
+
+    pub fn get_point(&self, t: f32) -> Vec2 {
+        let mt = 1.0 - t;
+
+        match *self {
+            Segment::Line(a, b) => a * mt + b * t,
+            Segment::Quad(a, b, c) => a * (mt * mt) + b * (2.0 * mt * t) + c * (t * t),
+            Segment::Cubic(a, b, c, d) => {
+                a * (mt * mt * mt) + b * (3.0 * mt * mt * t) + c * (3.0 * mt * t * t) + d * (t * t * t)
+            }
+        }
+    }
+
+    /// Bounds of the control points, the curve is always inside them
+    pub fn get_bounds(&self) -> (Vec2, Vec2) {
+        match *self {
+            Segment::Line(a, b) => (a.min(b), a.max(b)),
+            Segment::Quad(a, b, c) => (a.min(b).min(c), a.max(b).max(c)),
+            Segment::Cubic(a, b, c, d) => (a.min(b).min(c).min(d), a.max(b).max(c).max(d)),
+        }
+    }
+
+    /// `t` of the closest point on the segment
+    fn get_closest_t(&self, point: Vec2) -> f32 {
+        match *self {
+            Segment::Line(a, b) => {
+                let edge = b - a;
+
+                if edge.length_squared() > 0.0 {
+                    ((point - a).dot(edge) / edge.length_squared()).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                }
+            }
+            Segment::Quad(a, b, c) => {
+                // Closest point has `(B(t) - point)·B'(t) = 0`, a cubic in `t`
+                let ab = b - a;
+                let curve = a - b * 2.0 + c;
+                let start = a - point;
+                let mut roots = [0.0; 3];
+                let count = solve_cubic(
+                    curve.dot(curve) as f64,
+                    (3.0 * ab.dot(curve)) as f64,
+                    (2.0 * ab.dot(ab) + start.dot(curve)) as f64,
+                    start.dot(ab) as f64,
+                    &mut roots,
+                );
+
+                roots
+                    .iter()
+                    .take(count)
+                    .map(|&t| (t as f32).clamp(0.0, 1.0))
+                    .chain([0.0, 1.0].iter().cloned())
+                    .fold((0.0, f32::INFINITY), |closest, t| {
+                        let distance = (self.get_point(t) - point).length_squared();
+                        if distance < closest.1 { (t, distance) } else { closest }
+                    })
+                    .0
+            }
+            Segment::Cubic(a, b, c, d) => {
+                // Closest point has `(B(t) - point)·B'(t) = 0`, a quintic in `t` with no closed form.
+                // `B(t) = start + b1·t + b2·t² + b3·t³`
+                let to_f64 = |v: Vec2| (v.x as f64, v.y as f64);
+                let dot = |u: (f64, f64), v: (f64, f64)| u.0 * v.0 + u.1 * v.1;
+                let start = to_f64(a - point);
+                let b1 = to_f64((b - a) * 3.0);
+                let b2 = to_f64((c - b * 2.0 + a) * 3.0);
+                let b3 = to_f64(d - c * 3.0 + b * 3.0 - a);
+                let mut roots = [0.0; 5];
+                let count = solve_polynomial(
+                    &[
+                        dot(start, b1),
+                        2.0 * dot(start, b2) + dot(b1, b1),
+                        3.0 * dot(start, b3) + 3.0 * dot(b1, b2),
+                        4.0 * dot(b1, b3) + 2.0 * dot(b2, b2),
+                        5.0 * dot(b2, b3),
+                        3.0 * dot(b3, b3),
+                    ],
+                    0.0,
+                    1.0,
+                    &mut roots,
+                );
+
+                roots
+                    .iter()
+                    .take(count)
+                    .map(|&t| t as f32)
+                    .chain([0.0, 1.0].iter().cloned())
+                    .fold((0.0, f32::INFINITY), |closest, t| {
+                        let distance = (self.get_point(t) - point).length_squared();
+                        if distance < closest.1 { (t, distance) } else { closest }
+                    })
+                    .0
+            }
+        }
+    }
+
+    /// Unsigned distance from the point to the segment
+    pub fn get_distance(&self, point: Vec2) -> f32 {
+        (self.get_point(self.get_closest_t(point)) - point).length()
+    }
+
+    /// Signed number of times the segment crosses the horizontal ray that goes from the point
+    /// to `+x`. Upwards crossings count `+1`, downwards `-1`.
+    pub fn get_winding(&self, point: Vec2) -> i32 {
+        let (min, max) = self.get_bounds();
+
+        if max.x < point.x || min.y > point.y || max.y < point.y {
+            return 0;
+        }
+
+        // Split the curve in pieces monotonic in y, each piece can cross the ray once
+        let mut splits = [0.0f32; 5];
+        let mut count = 1;
+        let mut roots = [0.0; 3];
+        let root_count = match *self {
+            Segment::Line(_, _) => 0,
+            Segment::Quad(a, b, c) => solve_quadratic(0.0, (a.y - b.y * 2.0 + c.y) as f64, (b.y - a.y) as f64, &mut roots),
+            Segment::Cubic(a, b, c, d) => solve_quadratic(
+                (d.y - c.y * 3.0 + b.y * 3.0 - a.y) as f64,
+                (2.0 * (c.y - b.y * 2.0 + a.y)) as f64,
+                (b.y - a.y) as f64,
+                &mut roots,
+            ),
+        };
+
+        for &t in roots.iter().take(root_count) {
+            if t > 0.0 && t < 1.0 {
+                splits[count] = t as f32;
+                count += 1;
+            }
+        }
+
+        splits[1..count].sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        splits[count] = 1.0;
+
+        let mut winding = 0;
+
+        for piece in splits[..=count].windows(2) {
+            let (mut t0, mut t1) = (piece[0], piece[1]);
+            let a = if t0 == 0.0 { self.get_start() } else { self.get_point(t0) };
+            let b = if t1 == 1.0 { self.get_end() } else { self.get_point(t1) };
+
+            // Same rules as a straight edge, start point included & end point excluded going up
+            let direction = if a.y <= point.y && b.y > point.y {
+                1
+            } else if a.y > point.y && b.y <= point.y {
+                -1
+            } else {
+                continue;
+            };
+
+            // Bisect the crossing
+            for _ in 0..24 {
+                let t = (t0 + t1) * 0.5;
+                let below = self.get_point(t).y <= point.y;
+
+                if below == (direction == 1) { t0 = t } else { t1 = t }
+            }
+
+            if self.get_point((t0 + t1) * 0.5).x > point.x {
+                winding += direction;
+            }
+        }
+
+        winding
+    }
+}
+
+/// Closed contours made of segments
+#[derive(Debug, Clone)]
+pub struct Outline {
+    pub contours: Vec<Vec<Segment>>,
+    start: Vec2,
+    last: Vec2,
+}
+
+impl Outline {
+    pub fn new() -> Outline {
+        Outline { contours: vec![], start: Vec2::zero(), last: Vec2::zero() }
+    }
+
+    fn push(&mut self, segment: Segment) {
+        if let Some(contour) = self.contours.last_mut() {
+            contour.push(segment);
+        }
+
+        self.last = segment.get_end();
+    }
+
+    pub fn get_bounds(&self) -> Option<(Vec2, Vec2)> {
+        let mut bounds = self.contours.iter().flatten().map(|segment| segment.get_bounds());
+        let first = bounds.next()?;
+
+        Some(bounds.fold(first, |(min, max), (segment_min, segment_max)| (min.min(segment_min), max.max(segment_max))))
+    }
+
+    /// Unsigned distance to the closest segment, `f32::INFINITY` when empty
+    pub fn get_unsigned_distance(&self, point: Vec2) -> f32 {
+        let mut distance = f32::INFINITY;
+
+        for segment in self.contours.iter().flatten() {
+            // The curve is inside its control points hull, skip it if the hull is already too far
+            let (min, max) = segment.get_bounds();
+
+            if (point - point.max(min).min(max)).length() < distance {
+                distance = distance.min(segment.get_distance(point));
+            }
+        }
+
+        distance
+    }
+
+    /// Signed distance to the outline, negative inside (non-zero winding rule)
+    pub fn get_distance(&self, point: Vec2) -> f32 {
+        let distance = self.get_unsigned_distance(point);
+        let winding: i32 = self.contours.iter().flatten().map(|segment| segment.get_winding(point)).sum();
+
+        if winding != 0 { -distance } else { distance }
+    }
+}
+
+impl ttf::OutlineBuilder for Outline {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.contours.push(vec![]);
+        self.start = Vec2::new(x, y);
+        self.last = self.start;
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.push(Segment::Line(self.last, Vec2::new(x, y)));
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        self.push(Segment::Quad(self.last, Vec2::new(x1, y1), Vec2::new(x, y)));
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        self.push(Segment::Cubic(self.last, Vec2::new(x1, y1), Vec2::new(x2, y2), Vec2::new(x, y)));
+    }
+
+    fn close(&mut self) {
+        if self.last != self.start {
+            self.push(Segment::Line(self.last, self.start));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ttf::OutlineBuilder;
+
+    fn get_brute_force_distance(segment: &Segment, point: Vec2) -> f32 {
+        (0..=10000)
+            .map(|step| (segment.get_point(step as f32 / 10000.0) - point).length())
+            .fold(f32::INFINITY, f32::min)
+    }
+
+    #[test]
+    fn test_solve_cubic() {
+        let mut roots = [0.0; 3];
+
+        // (t - 1)(t - 2)(t - 3)
+        let count = solve_cubic(1.0, -6.0, 11.0, -6.0, &mut roots);
+        let mut sorted = roots[..count].to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        assert_eq!(count, 3);
+        assert!(sorted.iter().zip(&[1.0, 2.0, 3.0]).all(|(a, b)| (a - b).abs() < 1e-9));
+
+        // t³ + t + 2 = (t + 1)(t² - t + 2)
+        assert_eq!(solve_cubic(1.0, 0.0, 1.0, 2.0, &mut roots), 1);
+        assert!((roots[0] + 1.0).abs() < 1e-9);
+
+        // Degenerated into 2t - 4
+        assert_eq!(solve_cubic(0.0, 0.0, 2.0, -4.0, &mut roots), 1);
+        assert_eq!(roots[0], 2.0);
+    }
+
+    #[test]
+    fn test_solve_polynomial() {
+        let mut roots = [0.0; 5];
+
+        // (t - 0.1)(t - 0.2)(t - 0.5)(t - 0.9)(t - 3) = t⁵ - 4.7t⁴ + 5.99t³ - 2.833t² + 0.498t - 0.027
+        let count = solve_polynomial(&[-0.027, 0.498, -2.833, 5.99, -4.7, 1.0], 0.0, 1.0, &mut roots);
+
+        assert_eq!(count, 4);
+        assert!(roots[..count].iter().zip(&[0.1, 0.2, 0.5, 0.9]).all(|(a, b)| (a - b).abs() < 1e-9), "{:?}", roots);
+
+        // Double root, tangent to zero: (t - 0.5)²
+        let count = solve_polynomial(&[0.25, -1.0, 1.0], 0.0, 1.0, &mut roots);
+
+        assert_eq!(count, 1);
+        assert!((roots[0] - 0.5).abs() < 1e-9);
+
+        // Roots at the interval limits & no roots
+        assert_eq!(solve_polynomial(&[0.0, 1.0, -1.0], 0.0, 1.0, &mut roots), 2);
+        assert_eq!(solve_polynomial(&[1.0, 0.0, 1.0], 0.0, 1.0, &mut roots), 0);
+    }
+
+    #[test]
+    fn test_cubic_distance() {
+        // Tight S-shaped curve, the distance function has several local minimums
+        let segment = Segment::Cubic(Vec2::new(0.0, 0.0), Vec2::new(25.0, 12.0), Vec2::new(-15.0, -12.0), Vec2::new(10.0, 0.0));
+
+        for j in -20..=20 {
+            for i in -20..=20 {
+                let point = Vec2::new(i as f32 * 0.75 + 5.0, j as f32 * 0.5);
+                let expected = get_brute_force_distance(&segment, point);
+
+                assert!((segment.get_distance(point) - expected).abs() < 0.001, "{:?} {} {}", point, segment.get_distance(point), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_segment_distance() {
+        let segments = [
+            Segment::Line(Vec2::new(0.0, 0.0), Vec2::new(10.0, 5.0)),
+            Segment::Quad(Vec2::new(0.0, 0.0), Vec2::new(5.0, 10.0), Vec2::new(10.0, 0.0)),
+            Segment::Quad(Vec2::new(0.0, 0.0), Vec2::new(5.0, 0.0), Vec2::new(10.0, 0.0)),
+            Segment::Cubic(Vec2::new(0.0, 0.0), Vec2::new(0.0, 10.0), Vec2::new(10.0, -10.0), Vec2::new(10.0, 0.0)),
+            Segment::Cubic(Vec2::new(0.0, 0.0), Vec2::new(12.0, 10.0), Vec2::new(-2.0, 10.0), Vec2::new(10.0, 0.0)),
+        ];
+
+        for segment in segments.iter() {
+            for j in -4..=4 {
+                for i in -4..=4 {
+                    let point = Vec2::new(i as f32 * 3.3 + 5.0, j as f32 * 3.1);
+                    let expected = get_brute_force_distance(segment, point);
+
+                    assert!((segment.get_distance(point) - expected).abs() < 0.01, "{:?} {:?}", segment, point);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_outline_distance() {
+        // 10x10 square with a 4x4 hole
+        let mut outline = Outline::new();
+
+        outline.move_to(0.0, 0.0);
+        outline.line_to(10.0, 0.0);
+        outline.line_to(10.0, 10.0);
+        outline.line_to(0.0, 10.0);
+        outline.close();
+        outline.move_to(3.0, 3.0);
+        outline.line_to(3.0, 7.0);
+        outline.line_to(7.0, 7.0);
+        outline.line_to(7.0, 3.0);
+        outline.close();
+
+        assert_eq!(outline.get_bounds(), Some((Vec2::new(0.0, 0.0), Vec2::new(10.0, 10.0))));
+        assert_eq!(outline.get_distance(Vec2::new(1.0, 5.0)), -1.0);
+        assert_eq!(outline.get_distance(Vec2::new(5.0, 5.0)), 2.0);
+        assert_eq!(outline.get_distance(Vec2::new(-3.0, 14.0)), 5.0);
+
+        // Ray passing through vertices
+        assert_eq!(outline.get_distance(Vec2::new(-1.0, 10.0)), 1.0);
+        assert_eq!(outline.get_distance(Vec2::new(1.0, 3.0)), -1.0);
+    }
+
+    #[test]
+    fn test_outline_curves() {
+        // Counter-clockwise circle with radius 10 made of cubic curves, plus a clockwise quadratic "lens" hole
+        let k = 10.0 * 0.552_284_8;
+        let mut outline = Outline::new();
+
+        outline.move_to(10.0, 0.0);
+        outline.curve_to(10.0, k, k, 10.0, 0.0, 10.0);
+        outline.curve_to(-k, 10.0, -10.0, k, -10.0, 0.0);
+        outline.curve_to(-10.0, -k, -k, -10.0, 0.0, -10.0);
+        outline.curve_to(k, -10.0, 10.0, -k, 10.0, 0.0);
+        outline.close();
+        outline.move_to(-4.0, 0.0);
+        outline.quad_to(0.0, 4.0, 4.0, 0.0);
+        outline.quad_to(0.0, -4.0, -4.0, 0.0);
+        outline.close();
+
+        assert!((outline.get_distance(Vec2::new(20.0, 0.0)) - 10.0).abs() < 0.01);
+        assert!((outline.get_distance(Vec2::new(0.0, -7.0)) + 3.0).abs() < 0.01);
+        assert!((outline.get_distance(Vec2::new(0.0, 0.0)) - 2.0).abs() < 0.01);
+        assert!((outline.get_distance(Vec2::new(6.0, 6.0)) - (72.0f32.sqrt() - 10.0)).abs() < 0.02);
+
+        // Sign matches a point-in-circle test everywhere outside the lens
+        for j in -12..=12 {
+            for i in -12..=12 {
+                let point = Vec2::new(i as f32 + 0.5, j as f32 + 0.5);
+
+                if point.y.abs() > 2.5 || point.x.abs() > 4.0 {
+                    assert_eq!(outline.get_distance(point) < 0.0, point.length() < 10.0, "{:?}", point);
+                }
+            }
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use bevy_math::Vec2;
 
 use crate::distortion::*;
+use crate::modifier::*;
 use crate::transform::*;
 use crate::utils::*;
 
@@ -8,6 +9,14 @@ pub trait SDF {
     fn get_distance(&self, arena: &Vec<Object>, point: Vec2) -> f32;
 }
 
+/// Distance gradient by central differences, its length should be `1.0` for a true distance field
+pub fn get_gradient(sdf: &dyn SDF, arena: &Vec<Object>, point: Vec2, epsilon: f32) -> Vec2 {
+    let dx = sdf.get_distance(arena, point + Vec2::new(epsilon, 0.0)) - sdf.get_distance(arena, point - Vec2::new(epsilon, 0.0));
+    let dy = sdf.get_distance(arena, point + Vec2::new(0.0, epsilon)) - sdf.get_distance(arena, point - Vec2::new(0.0, epsilon));
+
+    Vec2::new(dx, dy) / (2.0 * epsilon)
+}
+
 pub struct Object<'a> {
     pub transform: Transform,
     // Meaning:
@@ -16,208 +25,2517 @@ pub struct Object<'a> {
     // - `Sync + Send`: Mark with Sync/Send to tell the compiler that it's OK for concurrency
     // - `'a`: Specify lifetime, boxed value will live as long as `Object`, otherwise `Box` defaults to `'static`
     pub distortion: Vec<Box<dyn Distorsion + Sync + Send + 'a>>,
+    // Applied in order to the distance, e.g. to turn a shape into its outline
+    pub modifier: Vec<Box<dyn Modifier + Sync + Send + 'a>>,
     pub parent_id: Option<usize>,
     pub sdf: Box<dyn SDF + Sync + Send + 'a>,
 }
 
-impl<'a> SDF for Object<'a> {
-    fn get_distance(&self, arena: &Vec<Object>, point: Vec2) -> f32 {
+impl<'a> Object<'a> {
+    /// Maps a point to the space the `sdf` is evaluated in, also returns how much the distortions stretch the space
+    fn map_point(&self, point: Vec2) -> (Vec2, f32) {
         // Transform point
         let mut point = self.transform.map(point);
 
-        // Apply distortion
-        for dist in &self.distortion {
-            point = dist.map(point);
+        // Apply distortion, keeping track of how much it stretches the space
+        let mut lipschitz = 1.0;
+
+        for dist in &self.distortion {
+            lipschitz *= dist.get_lipschitz(point);
+            point = dist.map(point);
+        }
+
+        (point, lipschitz)
+    }
+
+    /// Maps a point in the parent space to the space the `sdf` is evaluated in (after the transform & distortions)
+    pub fn get_local_point(&self, point: Vec2) -> Vec2 {
+        self.map_point(point).0
+    }
+
+    /// Maps a world point to the parent space going through all the parents, like groups do with their children.
+    /// Also returns the factor that turns distances in the parent space into world distances.
+    pub fn get_parent_point(&self, arena: &Vec<Object>, point: Vec2) -> (Vec2, f32) {
+        match self.parent_id {
+            Some(id) => {
+                let parent = &arena[id];
+                let (point, scale) = parent.get_parent_point(arena, point);
+                let (point, lipschitz) = parent.map_point(point);
+
+                (point, scale * parent.transform.get_distance_scale() / lipschitz.max(f32::EPSILON))
+            }
+            None => (point, 1.0),
+        }
+    }
+}
+
+impl<'a> SDF for Object<'a> {
+    fn get_distance(&self, arena: &Vec<Object>, point: Vec2) -> f32 {
+        let (point, lipschitz) = self.map_point(point);
+        let mut distance = self.sdf.get_distance(arena, point) / lipschitz.max(f32::EPSILON);
+
+        // Apply modifiers
+        for modifier in &self.modifier {
+            distance = modifier.apply(distance);
+        }
+
+        distance * self.transform.get_distance_scale()
+    }
+}
+
+pub mod primitive {
+    use std::f32::consts::PI;
+    use std::sync::Arc;
+    use ttf_parser::OutlineBuilder;
+
+    use crate::font::{Char, Font, VectorFont};
+    use crate::outline::Outline;
+
+    use super::*;
+
+    pub struct Circle {
+        pub radius: f32,
+    }
+
+    impl SDF for Circle {
+        fn get_distance(&self, _arena: &Vec<Object>, point: Vec2) -> f32 {
+            point.length() - self.radius
+        }
+    }
+
+    pub struct Square {
+        pub size: Vec2,
+    }
+
+    impl SDF for Square {
+        fn get_distance(&self, _arena: &Vec<Object>, point: Vec2) -> f32 {
+            let d = point.abs() - self.size;
+            let a = Vec2::new(d.x.max(0.0), d.y.max(0.0));
+
+            a.length() + d.x.max(d.y).min(0.0)
+        }
+    }
+
+    /// Box with rounded corners, `size` is the half size (like `Square`)
+    pub struct RoundedBox {
+        pub size: Vec2,
+        pub radius: f32,
+    }
+
+    impl SDF for RoundedBox {
+        fn get_distance(&self, _arena: &Vec<Object>, point: Vec2) -> f32 {
+            let radius = self.radius.min(self.size.x).min(self.size.y);
+            let d = point.abs() - self.size + Vec2::splat(radius);
+            let a = Vec2::new(d.x.max(0.0), d.y.max(0.0));
+
+            a.length() + d.x.max(d.y).min(0.0) - radius
+        }
+    }
+
+    /// Segment from `a` to `b`, it has no inside so the distance is never negative
+    pub struct LineSegment {
+        pub a: Vec2,
+        pub b: Vec2,
+    }
+
+    impl SDF for LineSegment {
+        fn get_distance(&self, _arena: &Vec<Object>, point: Vec2) -> f32 {
+            get_segment_distance(point, self.a, self.b)
+        }
+    }
+
+    pub struct Capsule {
+        pub a: Vec2,
+        pub b: Vec2,
+        pub radius: f32,
+    }
+
+    impl SDF for Capsule {
+        fn get_distance(&self, _arena: &Vec<Object>, point: Vec2) -> f32 {
+            get_segment_distance(point, self.a, self.b) - self.radius
+        }
+    }
+
+    fn get_segment_distance(point: Vec2, a: Vec2, b: Vec2) -> f32 {
+        let pa = point - a;
+        let ba = b - a;
+        let length_squared = ba.length_squared();
+        let h = if length_squared > 0.0 { (pa.dot(ba) / length_squared).clamp(0.0, 1.0) } else { 0.0 };
+
+        (pa - ba * h).length()
+    }
+
+    /// Equilateral triangle pointing up, centered at its centroid. `size` is half the side length.
+    pub struct EquilateralTriangle {
+        pub size: f32,
+    }
+
+    impl SDF for EquilateralTriangle {
+        fn get_distance(&self, _arena: &Vec<Object>, point: Vec2) -> f32 {
+            let k = 3.0f32.sqrt();
+            let mut p = Vec2::new(point.x.abs() - self.size, point.y + self.size / k);
+
+            if p.x + k * p.y > 0.0 {
+                p = Vec2::new(p.x - k * p.y, -k * p.x - p.y) / 2.0;
+            }
+
+            p.x -= p.x.clamp(-2.0 * self.size, 0.0);
+
+            -p.length() * p.y.signum()
+        }
+    }
+
+    /// Isosceles triangle pointing up, centered in its bounding box.
+    /// `size.x` is half the base width and `size.y` the height.
+    pub struct IsoscelesTriangle {
+        pub size: Vec2,
+    }
+
+    impl SDF for IsoscelesTriangle {
+        fn get_distance(&self, _arena: &Vec<Object>, point: Vec2) -> f32 {
+            // Move the apex to the origin with the base below it at `y = size.y`
+            let q = self.size;
+            let p = Vec2::new(point.x.abs(), q.y / 2.0 - point.y);
+            let a = p - q * (p.dot(q) / q.length_squared()).clamp(0.0, 1.0);
+            let b = p - q * Vec2::new((p.x / q.x).clamp(0.0, 1.0), 1.0);
+            let distance = a.length_squared().min(b.length_squared());
+            let side = (p.y * q.x - p.x * q.y).min(q.y - p.y);
+
+            -distance.sqrt() * side.signum()
+        }
+    }
+
+    /// Arbitrary triangle, vertices can be in any order
+    pub struct Triangle {
+        pub a: Vec2,
+        pub b: Vec2,
+        pub c: Vec2,
+    }
+
+    impl SDF for Triangle {
+        fn get_distance(&self, _arena: &Vec<Object>, point: Vec2) -> f32 {
+            let edges = [(self.a, self.b - self.a), (self.b, self.c - self.b), (self.c, self.a - self.c)];
+            let (e0, e2) = (edges[0].1, edges[2].1);
+            let area = e0.x * e2.y - e0.y * e2.x;
+            let orientation = area.signum();
+            let mut distance = f32::MAX;
+            let mut side = f32::MAX;
+
+            for &(start, edge) in edges.iter() {
+                let v = point - start;
+                // Coincident vertices make a zero length edge, measure it as a point
+                let t = if edge.length_squared() < f32::EPSILON { 0.0 } else { (v.dot(edge) / edge.length_squared()).clamp(0.0, 1.0) };
+                let pq = v - edge * t;
+
+                distance = distance.min(pq.length_squared());
+                side = side.min(orientation * (v.x * edge.y - v.y * edge.x));
+            }
+
+            // Without area there's no inside
+            if area.abs() < f32::EPSILON {
+                return distance.sqrt();
+            }
+
+            -distance.sqrt() * side.signum()
+        }
+    }
+
+    /// Regular polygon with a flat edge at the top, `radius` is the distance to the vertices
+    pub struct RegularPolygon {
+        pub radius: f32,
+        pub sides: u32,
+    }
+
+    impl SDF for RegularPolygon {
+        fn get_distance(&self, _arena: &Vec<Object>, point: Vec2) -> f32 {
+            let angle = PI / self.sides.max(3) as f32;
+
+            // Rotate the point into the sector around the `+y` axis, where the edge is horizontal
+            let sector = (point.x.atan2(point.y) / (2.0 * angle)).round() * 2.0 * angle;
+            let (sin, cos) = sector.sin_cos();
+            let p = Vec2::new(point.x * cos - point.y * sin, point.x * sin + point.y * cos);
+
+            let half_side = self.radius * angle.sin();
+            let apothem = self.radius * angle.cos();
+            let edge = Vec2::new(p.x.clamp(-half_side, half_side), apothem);
+
+            (p - edge).length() * (p.y - apothem).signum()
+        }
+    }
+
+    /// Star with a tip at the top. `sharpness` goes from `2.0` (regular polygon) to `points` (sharpest).
+    pub struct Star {
+        pub radius: f32,
+        pub points: u32,
+        pub sharpness: f32,
+    }
+
+    impl SDF for Star {
+        fn get_distance(&self, _arena: &Vec<Object>, point: Vec2) -> f32 {
+            let points = self.points.max(2) as f32;
+            let an = PI / points;
+            let en = PI / self.sharpness.clamp(2.0, points);
+            let acs = Vec2::new(an.cos(), an.sin());
+            let ecs = Vec2::new(en.cos(), en.sin());
+
+            // Fold the point into half a tip
+            let bn = point.x.atan2(point.y).rem_euclid(2.0 * an) - an;
+            let mut p = point.length() * Vec2::new(bn.cos(), bn.sin().abs());
+
+            p -= self.radius * acs;
+            p += ecs * (-p.dot(ecs)).clamp(0.0, self.radius * acs.y / ecs.y);
+
+            p.length() * p.x.signum()
+        }
+    }
+
+    /// Ellipse, `size` holds the radius in each axis
+    pub struct Ellipse {
+        pub size: Vec2,
+    }
+
+    impl SDF for Ellipse {
+        fn get_distance(&self, _arena: &Vec<Object>, point: Vec2) -> f32 {
+            // Robust closest point by bisection (David Eberly, "Distance from a Point to an Ellipse"),
+            // computed in `f64` with the major axis in `x`
+            let (mut y0, mut y1) = (point.x.abs() as f64, point.y.abs() as f64);
+            let (mut e0, mut e1) = (self.size.x.abs() as f64, self.size.y.abs() as f64);
+
+            if e0 < e1 {
+                std::mem::swap(&mut y0, &mut y1);
+                std::mem::swap(&mut e0, &mut e1);
+            }
+
+            if e1 == 0.0 {
+                return get_segment_distance(Vec2::new(y0 as f32, y1 as f32), Vec2::zero(), Vec2::new(e0 as f32, 0.0));
+            }
+
+            let inside = (y0 / e0).powi(2) + (y1 / e1).powi(2) < 1.0;
+            let (x0, x1) = if y1 > 0.0 {
+                if y0 > 0.0 {
+                    let (z0, z1) = (y0 / e0, y1 / e1);
+                    let r0 = (e0 / e1).powi(2);
+                    let n0 = r0 * z0;
+                    let g = z0 * z0 + z1 * z1 - 1.0;
+                    let mut s0 = z1 - 1.0;
+                    let mut s1 = if g < 0.0 { 0.0 } else { (n0 * n0 + z1 * z1).sqrt() - 1.0 };
+                    let mut s = s0;
+
+                    for _ in 0..200 {
+                        s = (s0 + s1) / 2.0;
+
+                        if s == s0 || s == s1 {
+                            break;
+                        }
+
+                        let g = (n0 / (s + r0)).powi(2) + (z1 / (s + 1.0)).powi(2) - 1.0;
+
+                        if g > 0.0 {
+                            s0 = s;
+                        } else if g < 0.0 {
+                            s1 = s;
+                        } else {
+                            break;
+                        }
+                    }
+
+                    (r0 * y0 / (s + r0), y1 / (s + 1.0))
+                } else {
+                    (0.0, e1)
+                }
+            } else {
+                let numer = e0 * y0;
+                let denom = e0 * e0 - e1 * e1;
+
+                if numer < denom {
+                    let x = numer / denom;
+                    (e0 * x, e1 * (1.0 - x * x).sqrt())
+                } else {
+                    (e0, 0.0)
+                }
+            };
+
+            let distance = ((x0 - y0).powi(2) + (x1 - y1).powi(2)).sqrt() as f32;
+
+            if inside { -distance } else { distance }
+        }
+    }
+
+    /// Circular arc opening upwards, `angle` is the aperture in degrees
+    pub struct CircularArc {
+        pub radius: f32,
+        pub angle: f32,
+        pub thickness: f32,
+    }
+
+    impl SDF for CircularArc {
+        fn get_distance(&self, _arena: &Vec<Object>, point: Vec2) -> f32 {
+            let (sin, cos) = (self.angle.to_radians() / 2.0).sin_cos();
+            let end = Vec2::new(sin, cos) * self.radius;
+            let p = Vec2::new(point.x.abs(), point.y);
+
+            let distance = if cos * p.x > sin * p.y {
+                (p - end).length()
+            } else {
+                (p.length() - self.radius).abs()
+            };
+
+            distance - self.thickness / 2.0
+        }
+    }
+
+    /// Annulus, `thickness` is centered on `radius`
+    pub struct Ring {
+        pub radius: f32,
+        pub thickness: f32,
+    }
+
+    impl SDF for Ring {
+        fn get_distance(&self, _arena: &Vec<Object>, point: Vec2) -> f32 {
+            (point.length() - self.radius).abs() - self.thickness / 2.0
+        }
+    }
+
+    /// Circular sector opening upwards, `angle` is the aperture in degrees
+    pub struct Pie {
+        pub radius: f32,
+        pub angle: f32,
+    }
+
+    impl SDF for Pie {
+        fn get_distance(&self, _arena: &Vec<Object>, point: Vec2) -> f32 {
+            let (sin, cos) = (self.angle.to_radians() / 2.0).sin_cos();
+            let c = Vec2::new(sin, cos);
+            let p = Vec2::new(point.x.abs(), point.y);
+            let l = p.length() - self.radius;
+            let m = (p - c * p.dot(c).clamp(0.0, self.radius)).length();
+
+            l.max(m * (cos * p.x - sin * p.y).signum())
+        }
+    }
+
+    /// Intersection of two circles of `radius` centered at `(-offset, 0)` and `(offset, 0)`
+    pub struct Vesica {
+        pub radius: f32,
+        pub offset: f32,
+    }
+
+    impl SDF for Vesica {
+        fn get_distance(&self, _arena: &Vec<Object>, point: Vec2) -> f32 {
+            let p = point.abs();
+            let b = (self.radius * self.radius - self.offset * self.offset).max(0.0).sqrt();
+
+            if (p.y - b) * self.offset > p.x * b {
+                (p - Vec2::new(0.0, b)).length()
+            } else {
+                (p - Vec2::new(-self.offset, 0.0)).length() - self.radius
+            }
+        }
+    }
+
+    /// Rhombus, `size` holds the half diagonals
+    pub struct Rhombus {
+        pub size: Vec2,
+    }
+
+    impl SDF for Rhombus {
+        fn get_distance(&self, _arena: &Vec<Object>, point: Vec2) -> f32 {
+            let b = self.size;
+            let p = point.abs();
+            let q = b - 2.0 * p;
+            let h = ((q.x * b.x - q.y * b.y) / b.length_squared()).clamp(-1.0, 1.0);
+            let d = (p - 0.5 * b * Vec2::new(1.0 - h, 1.0 + h)).length();
+
+            d * (p.x * b.y + p.y * b.x - b.x * b.y).signum()
+        }
+    }
+
+    /// Isosceles trapezoid, `bottom` and `top` are half widths and `height` is the half height
+    pub struct Trapezoid {
+        pub bottom: f32,
+        pub top: f32,
+        pub height: f32,
+    }
+
+    impl SDF for Trapezoid {
+        fn get_distance(&self, _arena: &Vec<Object>, point: Vec2) -> f32 {
+            let k1 = Vec2::new(self.top, self.height);
+            let k2 = Vec2::new(self.top - self.bottom, 2.0 * self.height);
+            let p = Vec2::new(point.x.abs(), point.y);
+            let width = if p.y < 0.0 { self.bottom } else { self.top };
+            let ca = Vec2::new(p.x - p.x.min(width), p.y.abs() - self.height);
+            let cb = p - k1 + k2 * ((k1 - p).dot(k2) / k2.length_squared()).clamp(0.0, 1.0);
+            let sign = if cb.x < 0.0 && ca.y < 0.0 { -1.0 } else { 1.0 };
+
+            sign * ca.length_squared().min(cb.length_squared()).sqrt()
+        }
+    }
+
+    /// Heart with the bottom tip at the origin and `size` height
+    pub struct Heart {
+        pub size: f32,
+    }
+
+    impl SDF for Heart {
+        fn get_distance(&self, _arena: &Vec<Object>, point: Vec2) -> f32 {
+            let p = Vec2::new(point.x.abs(), point.y) / self.size;
+
+            let distance = if p.x + p.y > 1.0 {
+                (p - Vec2::new(0.25, 0.75)).length() - 2.0f32.sqrt() / 4.0
+            } else {
+                let a = (p - Vec2::new(0.0, 1.0)).length_squared();
+                let b = (p - Vec2::splat(0.5 * (p.x + p.y).max(0.0))).length_squared();
+
+                a.min(b).sqrt() * (p.x - p.y).signum()
+            };
+
+            distance * self.size
+        }
+    }
+
+    /// Plus sign, `size.x` is the half length of the arms and `size.y` their half thickness
+    pub struct Cross {
+        pub size: Vec2,
+    }
+
+    impl SDF for Cross {
+        fn get_distance(&self, _arena: &Vec<Object>, point: Vec2) -> f32 {
+            let mut p = point.abs();
+
+            if p.y > p.x {
+                p = Vec2::new(p.y, p.x);
+            }
+
+            let q = p - self.size;
+            let k = q.x.max(q.y);
+            let w = if k > 0.0 { q } else { Vec2::new(self.size.y - p.x, -k) };
+
+            k.signum() * Vec2::new(w.x.max(0.0), w.y.max(0.0)).length()
+        }
+    }
+
+    /// Straight segments indexed in a `BBoxGrid`, so polygons & polylines with
+    /// thousands of vertices only measure the segments around the point
+    struct Segments {
+        segments: Vec<(Vec2, Vec2)>,
+        grid: BBoxGrid,
+        /// Segments overlapping each grid row, enough to compute the winding number with a horizontal ray
+        rows: Vec<Vec<usize>>,
+    }
+
+    impl Segments {
+        fn new(points: &[Vec2], closed: bool) -> Segments {
+            let mut segments = points.windows(2).map(|pair| (pair[0], pair[1])).collect::<Vec<_>>();
+
+            if closed && points.len() > 2 {
+                segments.push((points[points.len() - 1], points[0]));
+            }
+
+            let bounds = segments.iter().map(|&(a, b)| (a.min(b), a.max(b))).collect::<Vec<_>>();
+            let (min, max) = bounds.iter().fold(
+                (Vec2::new(f32::MAX, f32::MAX), Vec2::new(f32::MIN, f32::MIN)),
+                |(min, max), &(a, b)| (min.min(a), max.max(b)),
+            );
+
+            // Around one segment per cell, capped to 256 cells per axis
+            let size = max - min;
+            let cell_size = (size.x * size.y / segments.len() as f32).sqrt().max(size.x.max(size.y) / 256.0);
+            let grid = BBoxGrid::new(&bounds, if cell_size > 0.0 { cell_size } else { 1.0 });
+            let mut rows = vec![vec![]; grid.rows];
+
+            for (index, &(a, b)) in bounds.iter().enumerate() {
+                let (_, start_row) = grid.get_cell_position(a);
+                let (_, end_row) = grid.get_cell_position(b);
+
+                for row in &mut rows[start_row..=end_row] {
+                    row.push(index);
+                }
+            }
+
+            Segments { segments, grid, rows }
+        }
+
+        /// Unsigned distance to the closest segment
+        fn get_distance(&self, point: Vec2) -> f32 {
+            let outside_distance = self.grid.get_outside_distance(point);
+            let mut distance = f32::INFINITY;
+
+            for ring in 0..=self.grid.columns.max(self.grid.rows) {
+                if outside_distance.max((ring as f32 - 1.0) * self.grid.cell_size) >= distance {
+                    break;
+                }
+
+                self.grid.for_each_in_ring(point, ring, |index| {
+                    let (a, b) = self.segments[index];
+                    distance = distance.min(get_segment_distance(point, a, b));
+                });
+            }
+
+            distance
+        }
+
+        /// Non-zero when the point is inside the closed segments
+        fn get_winding(&self, point: Vec2) -> i32 {
+            if self.rows.is_empty() || point.y < self.grid.origin.y || point.y > self.grid.origin.y + self.grid.rows as f32 * self.grid.cell_size {
+                return 0;
+            }
+
+            let (_, row) = self.grid.get_cell_position(point);
+            let mut winding = 0;
+
+            for &index in &self.rows[row] {
+                let (a, b) = self.segments[index];
+                let side = (b.x - a.x) * (point.y - a.y) - (point.x - a.x) * (b.y - a.y);
+
+                if a.y <= point.y && b.y > point.y && side > 0.0 {
+                    winding += 1;
+                } else if a.y > point.y && b.y <= point.y && side < 0.0 {
+                    winding -= 1;
+                }
+            }
+
+            winding
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum PathCommand {
+        MoveTo(Vec2),
+        LineTo(Vec2),
+        /// Control point & end point
+        QuadTo(Vec2, Vec2),
+        /// Two control points & end point
+        CubicTo(Vec2, Vec2, Vec2),
+        /// Line back to the start of the subpath
+        Close,
+    }
+
+    /// Vector path made of lines & Bézier curves, like SVG paths. When every subpath is closed the
+    /// distance is signed (non-zero winding rule), otherwise the path is a stroke of `thickness`.
+    pub struct Path {
+        outline: Outline,
+        closed: bool,
+        /// Stroke width, only used by open paths
+        pub thickness: f32,
+    }
+
+    impl Path {
+        pub fn new(commands: &[PathCommand]) -> Path {
+            let mut outline = Outline::new();
+            let mut start = Vec2::zero();
+            let mut closed = true;
+            // Drawing without a `MoveTo` (at the beginning or after `Close`) starts a subpath at the last start point
+            let mut in_subpath = false;
+
+            for command in commands {
+                if !in_subpath && !matches!(command, PathCommand::MoveTo(_) | PathCommand::Close) {
+                    outline.move_to(start.x, start.y);
+                    in_subpath = true;
+                }
+
+                match *command {
+                    PathCommand::MoveTo(point) => {
+                        closed &= !(in_subpath && Path::has_segments(&outline));
+                        outline.move_to(point.x, point.y);
+                        start = point;
+                        in_subpath = true;
+                    }
+                    PathCommand::LineTo(point) => outline.line_to(point.x, point.y),
+                    PathCommand::QuadTo(control, point) => outline.quad_to(control.x, control.y, point.x, point.y),
+                    PathCommand::CubicTo(control_1, control_2, point) => {
+                        outline.curve_to(control_1.x, control_1.y, control_2.x, control_2.y, point.x, point.y)
+                    }
+                    PathCommand::Close => {
+                        if in_subpath {
+                            outline.close();
+                            in_subpath = false;
+                        }
+                    }
+                }
+            }
+
+            closed &= !(in_subpath && Path::has_segments(&outline));
+
+            Path { outline, closed, thickness: 0.0 }
+        }
+
+        /// Whether the last subpath has any segment
+        fn has_segments(outline: &Outline) -> bool {
+            matches!(outline.contours.last(), Some(contour) if !contour.is_empty())
+        }
+
+        pub fn is_closed(&self) -> bool {
+            self.closed
+        }
+    }
+
+    impl SDF for Path {
+        fn get_distance(&self, _arena: &Vec<Object>, point: Vec2) -> f32 {
+            let distance = if self.closed {
+                self.outline.get_distance(point)
+            } else {
+                self.outline.get_unsigned_distance(point) - self.thickness / 2.0
+            };
+
+            if distance.is_finite() {
+                distance
+            } else {
+                // Empty path
+                99999.0
+            }
+        }
+    }
+
+    /// Closed polygon, the inside follows the non-zero winding rule so self-intersecting polygons also work
+    pub struct Polygon {
+        segments: Segments,
+    }
+
+    impl Polygon {
+        pub fn new(points: Vec<Vec2>) -> Polygon {
+            Polygon { segments: Segments::new(&points, true) }
+        }
+    }
+
+    impl SDF for Polygon {
+        fn get_distance(&self, _arena: &Vec<Object>, point: Vec2) -> f32 {
+            let distance = self.segments.get_distance(point);
+
+            if !distance.is_finite() {
+                // Empty polygon
+                99999.0
+            } else if self.segments.get_winding(point) != 0 {
+                -distance
+            } else {
+                distance
+            }
+        }
+    }
+
+    /// Open line through the points, stroked with `thickness`
+    pub struct Polyline {
+        segments: Segments,
+        pub thickness: f32,
+    }
+
+    impl Polyline {
+        pub fn new(points: Vec<Vec2>, thickness: f32) -> Polyline {
+            // A single point is drawn as a dot
+            let points = if points.len() == 1 { vec![points[0], points[0]] } else { points };
+
+            Polyline { segments: Segments::new(&points, false), thickness }
+        }
+    }
+
+    impl SDF for Polyline {
+        fn get_distance(&self, _arena: &Vec<Object>, point: Vec2) -> f32 {
+            let distance = self.segments.get_distance(point);
+
+            if distance.is_finite() {
+                distance - self.thickness / 2.0
+            } else {
+                // Empty polyline
+                99999.0
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum TextAlign {
+        Left,
+        Center,
+        Right,
+        /// Stretch spaces so every line fills the block width, last line of each paragraph is left aligned
+        Justify,
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    pub struct TextLayout {
+        pub align: TextAlign,
+        /// Wrap words into a new line when a line gets wider than this
+        pub max_width: Option<f32>,
+        /// Multiplier of `Font::line_height`
+        pub line_spacing: f32,
+    }
+
+    impl TextLayout {
+        pub fn new() -> TextLayout {
+            TextLayout {
+                align: TextAlign::Left,
+                max_width: None,
+                line_spacing: 1.0,
+            }
+        }
+    }
+
+    pub struct Text {
+        bboxes: Vec<BBox>,
+        grid: BBoxGrid,
+        font: Arc<Font>,
+        layout: TextLayout,
+        size: f32,
+        text: String,
+    }
+
+    #[derive(Debug)]
+    struct BBox {
+        char: Char,
+        pos: Vec2,
+        size: Vec2,
+    }
+
+    impl BBox {
+        /// Closest point inside the bbox
+        pub fn clamp(&self, point: Vec2) -> Vec2 {
+            point.max(self.pos).min(self.pos + self.size)
+        }
+
+        /// Distance from the point to the bbox, `0.0` when inside
+        pub fn get_distance(&self, point: Vec2) -> f32 {
+            (point - self.clamp(point)).length()
+        }
+    }
+
+    /// Uniform grid over bboxes (`(min, max)` pairs), each cell lists the bboxes overlapping it.
+    /// Glyphs have similar sizes, so a grid with line-height cells keeps lookups to a handful of bboxes.
+    #[derive(Debug)]
+    struct BBoxGrid {
+        origin: Vec2,
+        cell_size: f32,
+        columns: usize,
+        rows: usize,
+        cells: Vec<Vec<usize>>,
+    }
+
+    impl BBoxGrid {
+        fn new(bboxes: &[(Vec2, Vec2)], cell_size: f32) -> BBoxGrid {
+            let mut min = Vec2::new(f32::MAX, f32::MAX);
+            let mut max = Vec2::new(f32::MIN, f32::MIN);
+
+            for &(bbox_min, bbox_max) in bboxes {
+                min = min.min(bbox_min);
+                max = max.max(bbox_max);
+            }
+
+            if bboxes.is_empty() || cell_size <= 0.0 {
+                return BBoxGrid { origin: Vec2::zero(), cell_size: 1.0, columns: 0, rows: 0, cells: vec![] };
+            }
+
+            let columns = (((max.x - min.x) / cell_size).floor() as usize) + 1;
+            let rows = (((max.y - min.y) / cell_size).floor() as usize) + 1;
+            let mut grid = BBoxGrid { origin: min, cell_size, columns, rows, cells: vec![vec![]; columns * rows] };
+
+            for (index, &(bbox_min, bbox_max)) in bboxes.iter().enumerate() {
+                let (start_column, start_row) = grid.get_cell_position(bbox_min);
+                let (end_column, end_row) = grid.get_cell_position(bbox_max);
+
+                for row in start_row..=end_row {
+                    for column in start_column..=end_column {
+                        grid.cells[row * columns + column].push(index);
+                    }
+                }
+            }
+
+            grid
+        }
+
+        /// Column & row of a point, clamped to the grid
+        fn get_cell_position(&self, point: Vec2) -> (usize, usize) {
+            let cell = (point - self.origin) / self.cell_size;
+            let column = (cell.x.max(0.0) as usize).min(self.columns - 1);
+            let row = (cell.y.max(0.0) as usize).min(self.rows - 1);
+
+            (column, row)
+        }
+
+        /// Distance from the point to the grid area, `0.0` when inside
+        fn get_outside_distance(&self, point: Vec2) -> f32 {
+            let max = self.origin + Vec2::new(self.columns as f32, self.rows as f32) * self.cell_size;
+
+            (point - point.max(self.origin).min(max)).length()
+        }
+
+        /// Calls `f` with the bbox indices of the cells at `ring` cells (Chebyshev distance) from
+        /// the point's cell. Ring `0` is the cell containing the point (or the closest one).
+        fn for_each_in_ring<F: FnMut(usize)>(&self, point: Vec2, ring: usize, mut f: F) {
+            if self.cells.is_empty() {
+                return;
+            }
+
+            let (column, row) = self.get_cell_position(point);
+            let (column, row, ring) = (column as isize, row as isize, ring as isize);
+
+            for r in (row - ring)..=(row + ring) {
+                if r < 0 || r >= self.rows as isize {
+                    continue;
+                }
+
+                // Top & bottom rows are full, the ones in between only have the first & last cells
+                let step = if r == row - ring || r == row + ring { 1 } else { (2 * ring).max(1) };
+                let mut c = column - ring;
+
+                while c <= column + ring {
+                    if c >= 0 && c < self.columns as isize {
+                        for &index in &self.cells[(r as usize) * self.columns + (c as usize)] {
+                            f(index);
+                        }
+                    }
+
+                    c += step;
+                }
+            }
+        }
+    }
+
+    impl Text {
+        pub fn new(text: String, size: f32, font: Arc<Font>) -> Text {
+            Text::with_layout(text, size, font, TextLayout::new())
+        }
+
+        pub fn with_layout(text: String, size: f32, font: Arc<Font>, layout: TextLayout) -> Text {
+            let grid = BBoxGrid::new(&[], 0.0);
+            let mut text = Text { bboxes: vec![], grid, font, layout, size, text };
+            text.generate_bboxes();
+            let bounds = text.bboxes.iter().map(|bbox| (bbox.pos, bbox.pos + bbox.size)).collect::<Vec<_>>();
+            text.grid = BBoxGrid::new(&bounds, text.font.line_height);
+            text
+        }
+
+        /// Scale from font space (`Font::size`) to world space (`Text::size`)
+        fn get_scale(&self) -> f32 {
+            self.size / self.font.size
+        }
+
+        /// `TextLayout::max_width` is in world space, layout happens in font space
+        fn get_max_width(&self) -> Option<f32> {
+            self.layout.max_width.map(|max_width| max_width / self.get_scale())
+        }
+
+        fn get_char_distance(&self, point: &Vec2, bbox: &BBox) -> f32 {
+            self.font.get_distance(&bbox.char, *point - bbox.pos) // BBox Space
+        }
+
+        /// Atlas distances saturate at half the distance range
+        fn get_padding(&self) -> f32 {
+            self.font.distance_range / 2.0
+        }
+
+        /// Distance to a glyph. Inside its bbox it's the atlas distance, outside it's a lower bound:
+        /// the glyph is, at least, as far as its bbox and, as distances change at most 1 unit per unit,
+        /// as far as the atlas distance at the closest bbox point minus the way to it.
+        fn get_glyph_distance(&self, point: Vec2, bbox: &BBox) -> f32 {
+            let closest = bbox.clamp(point);
+            let bbox_distance = (point - closest).length();
+            let char_distance = self.get_char_distance(&closest, bbox);
+
+            if bbox_distance > 0.0 {
+                bbox_distance.max(char_distance - bbox_distance)
+            } else {
+                char_distance
+            }
+        }
+
+        fn get_initial_cursor_position(&self, line: &str) -> f32 {
+            let first_char = line.chars().next();
+
+            match first_char {
+                Some(char) => -self.font.get_char(char).x_offset,
+                None => 0.0,
+            }
+        }
+
+        /// Width of a single line of text, including kerning
+        fn get_line_width(&self, line: &str) -> f32 {
+            let mut cursor = self.get_initial_cursor_position(line);
+            let mut previous: Option<char> = None;
+
+            for letter in line.chars() {
+                if let Some(previous) = previous {
+                    cursor += self.font.get_kerning(previous, letter);
+                }
+
+                cursor += self.font.get_char(letter).x_advance;
+                previous = Some(letter);
+            }
+
+            cursor
+        }
+
+        /// Split the text in lines, breaking on `\n` and wrapping words on `max_width`.
+        /// Returns each line and whether it ends a paragraph.
+        fn get_lines(&self) -> Vec<(String, bool)> {
+            let mut lines = vec![];
+
+            for paragraph in self.text.split('\n') {
+                let paragraph = paragraph.trim_end_matches('\r');
+                let max_width = match self.get_max_width() {
+                    Some(max_width) => max_width,
+                    None => {
+                        lines.push((paragraph.to_string(), true));
+                        continue;
+                    }
+                };
+
+                // Empty words are extra spaces, leading ones are kept as indentation
+                let mut words = paragraph.split(' ');
+                let mut line = words.next().unwrap_or("").to_string();
+
+                for word in words {
+                    let candidate = format!("{} {}", line, word);
+                    let is_line_empty = line.trim_start_matches(' ').is_empty();
+
+                    if !word.is_empty() && !is_line_empty && self.get_line_width(&candidate) > max_width {
+                        // Words wider than `max_width` get a line for themselves,
+                        // only the spaces at the break are dropped
+                        lines.push((line.trim_end_matches(' ').to_string(), false));
+                        line = word.to_string();
+                    } else {
+                        line = candidate;
+                    }
+                }
+
+                lines.push((line, true));
+            }
+
+            lines
+        }
+
+        fn generate_bboxes(&mut self) {
+            let lines = self.get_lines();
+            let widths = lines.iter().map(|(line, _)| self.get_line_width(line)).collect::<Vec<f32>>();
+            let block_width = match self.get_max_width() {
+                Some(max_width) => max_width,
+                None => widths.iter().cloned().fold(0.0, f32::max),
+            };
+            let line_height = self.font.line_height * self.layout.line_spacing;
+
+            for (index, (line, is_paragraph_end)) in lines.iter().enumerate() {
+                let y = index as f32 * line_height;
+                let free_space = block_width - widths[index];
+                let mut space_extra = 0.0;
+                let mut cursor = self.get_initial_cursor_position(line);
+                let mut previous: Option<char> = None;
+
+                match self.layout.align {
+                    TextAlign::Left => (),
+                    TextAlign::Center => cursor += free_space / 2.0,
+                    TextAlign::Right => cursor += free_space,
+                    TextAlign::Justify => {
+                        let spaces = line.matches(' ').count();
+
+                        if !is_paragraph_end && spaces > 0 {
+                            space_extra = free_space / spaces as f32;
+                        }
+                    }
+                }
+
+                for letter in line.chars() {
+                    let char = self.font.get_char(letter);
+                    let x_advance = char.x_advance;
+
+                    if let Some(previous) = previous {
+                        cursor += self.font.get_kerning(previous, letter);
+                    }
+
+                    // Spaces & other blank chars have nothing to render
+                    if char.width > 0.0 && char.height > 0.0 {
+                        self.bboxes.push(BBox {
+                            pos: Vec2::new(cursor + char.x_offset, y + char.y_offset),
+                            size: Vec2::new(char.width, char.height),
+                            char,
+                        });
+                    }
+
+                    cursor += x_advance;
+
+                    if letter == ' ' {
+                        cursor += space_extra;
+                    }
+
+                    previous = Some(letter);
+                }
+            }
+        }
+    }
+
+    impl SDF for Text {
+        fn get_distance(&self, _arena: &Vec<Object>, point: Vec2) -> f32 {
+            // Work in font space, distances are scaled back to world space
+            let scale = self.get_scale();
+            let point = Vec2::new(point.x, -point.y) / scale;
+            let outside_distance = self.grid.get_outside_distance(point);
+            let padding = self.get_padding();
+            let mut distance = f32::INFINITY;
+
+            // Visit the grid in rings around the point, stop once a ring is too far to improve the lowest distance
+            for ring in 0..=self.grid.columns.max(self.grid.rows) {
+                let ring_distance = outside_distance.max((ring as f32 - 1.0) * self.grid.cell_size);
+
+                if ring_distance - padding >= distance {
+                    break;
+                }
+
+                self.grid.for_each_in_ring(point, ring, |index| {
+                    let bbox = &self.bboxes[index];
+
+                    // Skip sampling glyphs that can't be closer
+                    if bbox.get_distance(point) - padding < distance {
+                        distance = distance.min(self.get_glyph_distance(point, bbox));
+                    }
+                });
+            }
+
+            if distance.is_finite() {
+                distance * scale
+            } else {
+                // Empty text
+                99999.0
+            }
+        }
+    }
+
+    /// Glyph of a `VectorText`, placed in font units
+    struct PlacedGlyph {
+        offset: Vec2,
+        min: Vec2,
+        max: Vec2,
+        outline: Outline,
+    }
+
+    /// Text evaluated from the glyph outlines, exact at any zoom level and far away from the glyphs.
+    /// Like `Text`, the origin is at the top-left corner of the first line.
+    pub struct VectorText {
+        glyphs: Vec<PlacedGlyph>,
+        /// Scale from font units to world space
+        scale: f32,
+    }
+
+    impl VectorText {
+        pub fn new(text: String, size: f32, font: Arc<VectorFont>) -> VectorText {
+            let mut glyphs = vec![];
+
+            for (line_index, line) in text.split('\n').enumerate() {
+                let baseline = -(line_index as f32 * font.line_height) - font.ascender;
+                let mut cursor = 0.0;
+                let mut previous: Option<char> = None;
+
+                for letter in line.trim_end_matches('\r').chars() {
+                    let glyph = match font.get_glyph(letter) {
+                        Some(glyph) => glyph,
+                        None => continue,
+                    };
+
+                    if let Some(previous) = previous {
+                        cursor += font.get_kerning(previous, letter);
+                    }
+
+                    if let Some((min, max)) = glyph.outline.get_bounds() {
+                        glyphs.push(PlacedGlyph {
+                            offset: Vec2::new(cursor, baseline),
+                            min,
+                            max,
+                            outline: glyph.outline.clone(),
+                        });
+                    }
+
+                    cursor += glyph.x_advance;
+                    previous = Some(letter);
+                }
+            }
+
+            VectorText { glyphs, scale: size / font.units_per_em }
+        }
+    }
+
+    impl SDF for VectorText {
+        fn get_distance(&self, _arena: &Vec<Object>, point: Vec2) -> f32 {
+            // Work in font units, distances are scaled back to world space
+            let point = point / self.scale;
+            let mut distance = f32::INFINITY;
+
+            for glyph in &self.glyphs {
+                let point = point - glyph.offset;
+                let bounds_distance = (point - point.max(glyph.min).min(glyph.max)).length();
+
+                // Outside its bounds a glyph is, at least, as far as the bounds
+                if bounds_distance > 0.0 && bounds_distance >= distance {
+                    continue;
+                }
+
+                distance = distance.min(glyph.outline.get_distance(point));
+            }
+
+            if distance.is_finite() {
+                distance * self.scale
+            } else {
+                // Empty text
+                99999.0
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn get_font() -> Arc<Font> {
+            Arc::new(Font::from_fnt("comic-sans.fnt").unwrap())
+        }
+
+        fn assert_distance(sdf: &dyn SDF, point: Vec2, expected: f32) {
+            let distance = sdf.get_distance(&vec![], point);
+            assert!((distance - expected).abs() < 1e-4, "distance at {:?} is {}, expected {}", point, distance, expected);
+        }
+
+        #[test]
+        fn test_rounded_box() {
+            let sdf = RoundedBox { size: Vec2::new(2.0, 1.0), radius: 0.5 };
+
+            assert_distance(&sdf, Vec2::zero(), -1.0);
+            assert_distance(&sdf, Vec2::new(3.0, 0.0), 1.0);
+            assert_distance(&sdf, Vec2::new(0.0, -1.5), 0.5);
+            assert_distance(&sdf, Vec2::new(3.0, 2.0), 1.5 * 2.0f32.sqrt() - 0.5);
+        }
+
+        #[test]
+        fn test_segment_and_capsule() {
+            let segment = LineSegment { a: Vec2::zero(), b: Vec2::new(2.0, 0.0) };
+            let capsule = Capsule { a: Vec2::zero(), b: Vec2::new(2.0, 0.0), radius: 0.5 };
+
+            assert_distance(&segment, Vec2::new(1.0, 1.0), 1.0);
+            assert_distance(&segment, Vec2::new(3.0, 0.0), 1.0);
+            assert_distance(&segment, Vec2::new(-1.0, -1.0), 2.0f32.sqrt());
+            assert_distance(&capsule, Vec2::new(1.0, 0.0), -0.5);
+            assert_distance(&capsule, Vec2::new(1.0, 1.0), 0.5);
+            assert_distance(&capsule, Vec2::new(3.0, 0.0), 0.5);
+        }
+
+        #[test]
+        fn test_triangles() {
+            let k = 3.0f32.sqrt();
+            let equilateral = EquilateralTriangle { size: 1.0 };
+
+            assert_distance(&equilateral, Vec2::zero(), -1.0 / k);
+            assert_distance(&equilateral, Vec2::new(1.0, -1.0 / k), 0.0);
+            assert_distance(&equilateral, Vec2::new(0.0, -1.0 / k - 1.0), 1.0);
+            assert_distance(&equilateral, Vec2::new(0.0, 2.0 / k + 1.0), 1.0);
+
+            let isosceles = IsoscelesTriangle { size: Vec2::new(1.0, 2.0) };
+
+            assert_distance(&isosceles, Vec2::new(0.0, -2.0), 1.0);
+            assert_distance(&isosceles, Vec2::new(0.0, 2.0), 1.0);
+            assert_distance(&isosceles, Vec2::new(0.0, -0.5), -0.5);
+            assert_distance(&isosceles, Vec2::new(2.0, -1.0), 1.0);
+
+            // Same distances regardless of the winding
+            for triangle in &[
+                Triangle { a: Vec2::zero(), b: Vec2::new(4.0, 0.0), c: Vec2::new(0.0, 3.0) },
+                Triangle { a: Vec2::zero(), b: Vec2::new(0.0, 3.0), c: Vec2::new(4.0, 0.0) },
+            ] {
+                assert_distance(triangle, Vec2::new(1.0, 1.0), -1.0);
+                assert_distance(triangle, Vec2::new(-1.0, 0.0), 1.0);
+                assert_distance(triangle, Vec2::new(4.0, 3.0), 12.0 / 5.0);
+                assert_distance(triangle, Vec2::new(-3.0, -4.0), 5.0);
+            }
+
+            // Degenerate triangles are measured as a segment or a point
+            for triangle in &[
+                Triangle { a: Vec2::zero(), b: Vec2::zero(), c: Vec2::new(4.0, 0.0) },
+                Triangle { a: Vec2::zero(), b: Vec2::new(4.0, 0.0), c: Vec2::new(4.0, 0.0) },
+            ] {
+                assert_distance(triangle, Vec2::new(2.0, 1.0), 1.0);
+                assert_distance(triangle, Vec2::new(2.0, -1.0), 1.0);
+                assert_distance(triangle, Vec2::new(-3.0, -4.0), 5.0);
+            }
+
+            assert_distance(&Triangle { a: Vec2::new(1.0, 1.0), b: Vec2::new(1.0, 1.0), c: Vec2::new(1.0, 1.0) }, Vec2::new(4.0, 5.0), 5.0);
+        }
+
+        #[test]
+        fn test_regular_polygon() {
+            let square = RegularPolygon { radius: 2.0f32.sqrt(), sides: 4 };
+
+            assert_distance(&square, Vec2::zero(), -1.0);
+            assert_distance(&square, Vec2::new(2.0, 0.0), 1.0);
+            assert_distance(&square, Vec2::new(0.0, -0.5), -0.5);
+            assert_distance(&square, Vec2::new(2.0, 2.0), 2.0f32.sqrt());
+
+            let hexagon = RegularPolygon { radius: 1.0, sides: 6 };
+
+            assert_distance(&hexagon, Vec2::zero(), -(PI / 6.0).cos());
+            assert_distance(&hexagon, Vec2::new(0.0, 2.0), 2.0 - (PI / 6.0).cos());
+            assert_distance(&hexagon, Vec2::new(-(PI / 6.0).sin() * 3.0, (PI / 6.0).cos() * 3.0), 2.0);
+        }
+
+        #[test]
+        fn test_star() {
+            let star = Star { radius: 1.0, points: 5, sharpness: 2.5 };
+
+            assert_distance(&star, Vec2::new(0.0, 1.0), 0.0);
+            assert_distance(&star, Vec2::new(0.0, 2.0), 1.0);
+            assert!(star.get_distance(&vec![], Vec2::zero()) < 0.0);
+
+            // With `sharpness == 2.0` it's a regular polygon, upside down compared to `RegularPolygon`
+            let pentagon = Star { radius: 1.0, points: 5, sharpness: 2.0 };
+            let polygon = RegularPolygon { radius: 1.0, sides: 5 };
+
+            for &point in &[Vec2::zero(), Vec2::new(0.0, -2.0), Vec2::new(0.3, -0.2), Vec2::new(1.5, 0.7)] {
+                assert_distance(&pentagon, point, polygon.get_distance(&vec![], point * Vec2::new(1.0, -1.0)));
+            }
+        }
+
+        #[test]
+        fn test_ellipse() {
+            let ellipse = Ellipse { size: Vec2::new(2.0, 1.0) };
+
+            assert_distance(&ellipse, Vec2::zero(), -1.0);
+            assert_distance(&ellipse, Vec2::new(3.0, 0.0), 1.0);
+            assert_distance(&ellipse, Vec2::new(0.0, -2.0), 1.0);
+            assert_distance(&ellipse, Vec2::new(-1.5, 0.0), -0.5);
+            assert_distance(&Ellipse { size: Vec2::new(1.0, 1.0) }, Vec2::new(2.0, 0.0), 1.0);
+
+            // Compare against the closest sampled point
+            for &point in &[Vec2::new(1.0, 1.0), Vec2::new(-2.5, 0.3), Vec2::new(0.4, 0.2), Vec2::new(1.9, -0.1)] {
+                let closest = (0..10000)
+                    .map(|i| {
+                        let (sin, cos) = (i as f32 / 10000.0 * 2.0 * PI).sin_cos();
+                        (Vec2::new(cos * 2.0, sin) - point).length()
+                    })
+                    .fold(f32::MAX, f32::min);
+                let distance = ellipse.get_distance(&vec![], point);
+
+                assert!((distance.abs() - closest).abs() < 1e-3, "{} != {}", distance, closest);
+            }
+        }
+
+        #[test]
+        fn test_arc_ring_and_pie() {
+            let arc = CircularArc { radius: 1.0, angle: 180.0, thickness: 0.2 };
+
+            assert_distance(&arc, Vec2::new(0.0, 1.0), -0.1);
+            assert_distance(&arc, Vec2::new(0.0, 0.0), 0.9);
+            assert_distance(&arc, Vec2::new(0.0, -1.0), 2.0f32.sqrt() - 0.1);
+
+            let ring = Ring { radius: 1.0, thickness: 0.2 };
+
+            assert_distance(&ring, Vec2::zero(), 0.9);
+            assert_distance(&ring, Vec2::new(1.0, 0.0), -0.1);
+            assert_distance(&ring, Vec2::new(0.0, -2.0), 0.9);
+
+            let pie = Pie { radius: 1.0, angle: 90.0 };
+
+            assert_distance(&pie, Vec2::new(0.0, 0.5), -0.5 * (PI / 4.0).sin());
+            assert_distance(&pie, Vec2::new(0.0, -1.0), 1.0);
+            assert_distance(&pie, Vec2::new(0.0, 2.0), 1.0);
+        }
+
+        #[test]
+        fn test_vesica_and_rhombus() {
+            let vesica = Vesica { radius: 2.0, offset: 1.0 };
+
+            assert_distance(&vesica, Vec2::zero(), -1.0);
+            assert_distance(&vesica, Vec2::new(2.0, 0.0), 1.0);
+            assert_distance(&vesica, Vec2::new(0.0, 3.0), 3.0 - 3.0f32.sqrt());
+
+            let rhombus = Rhombus { size: Vec2::new(2.0, 1.0) };
+
+            assert_distance(&rhombus, Vec2::zero(), -2.0 / 5.0f32.sqrt());
+            assert_distance(&rhombus, Vec2::new(3.0, 0.0), 1.0);
+            assert_distance(&rhombus, Vec2::new(0.0, -2.0), 1.0);
+        }
+
+        #[test]
+        fn test_trapezoid() {
+            let trapezoid = Trapezoid { bottom: 2.0, top: 1.0, height: 1.0 };
+
+            assert_distance(&trapezoid, Vec2::zero(), -1.0);
+            assert_distance(&trapezoid, Vec2::new(0.0, 2.0), 1.0);
+            assert_distance(&trapezoid, Vec2::new(0.0, -3.0), 2.0);
+            assert_distance(&trapezoid, Vec2::new(1.5, 0.0), (3.0 - 3.0) / 5.0f32.sqrt());
+            assert_distance(&trapezoid, Vec2::new(3.0, -1.0), 1.0);
+        }
+
+        #[test]
+        fn test_heart_and_cross() {
+            let heart = Heart { size: 2.0 };
+
+            assert_distance(&heart, Vec2::zero(), 0.0);
+            assert_distance(&heart, Vec2::new(0.0, -1.0), 1.0);
+            assert_distance(&heart, Vec2::new(0.0, 1.0), -0.5f32.sqrt());
+
+            let cross = Cross { size: Vec2::new(2.0, 0.5) };
+
+            assert_distance(&cross, Vec2::zero(), -0.5f32.sqrt());
+            assert_distance(&cross, Vec2::new(3.0, 0.0), 1.0);
+            assert_distance(&cross, Vec2::new(0.0, -3.0), 1.0);
+            assert_distance(&cross, Vec2::new(1.0, 1.0), 0.5);
+            assert_distance(&cross, Vec2::new(1.5, 0.2), -0.3);
+        }
+
+        #[test]
+        fn test_path() {
+            use PathCommand::*;
+
+            // Closed square with a curved right side
+            let path = Path::new(&[
+                MoveTo(Vec2::new(-1.0, -1.0)),
+                LineTo(Vec2::new(1.0, -1.0)),
+                QuadTo(Vec2::new(2.0, 0.0), Vec2::new(1.0, 1.0)),
+                LineTo(Vec2::new(-1.0, 1.0)),
+                Close,
+            ]);
+
+            assert!(path.is_closed());
+            assert_distance(&path, Vec2::zero(), -1.0);
+            assert_distance(&path, Vec2::new(-3.0, 0.0), 2.0);
+            assert_distance(&path, Vec2::new(2.0, 0.0), 0.5);
+            assert_distance(&path, Vec2::new(1.2, 0.0), -0.3);
+
+            // Open cubic arc, stroked
+            let k = 0.552_284_8;
+            let mut path = Path::new(&[MoveTo(Vec2::new(1.0, 0.0)), CubicTo(Vec2::new(1.0, k), Vec2::new(k, 1.0), Vec2::new(0.0, 1.0))]);
+            path.thickness = 0.5;
+
+            assert!(!path.is_closed());
+            assert!((path.get_distance(&vec![], Vec2::new(2.0, 2.0)) - (8.0f32.sqrt() - 1.25)).abs() < 1e-3);
+            assert_distance(&path, Vec2::zero(), 0.75);
+            assert_distance(&path, Vec2::new(-1.0, 1.0), 0.75);
+
+            // Drawing after `Close` starts a new subpath at the same start point, which is left open
+            let path = Path::new(&[
+                MoveTo(Vec2::zero()),
+                LineTo(Vec2::new(1.0, 0.0)),
+                LineTo(Vec2::new(0.0, 1.0)),
+                Close,
+                LineTo(Vec2::new(-1.0, 0.0)),
+            ]);
+
+            assert!(!path.is_closed());
+            assert_distance(&path, Vec2::new(-0.5, -1.0), 1.0);
+            assert_distance(&path, Vec2::new(0.25, 0.25), 0.25);
+
+            assert_eq!(Path::new(&[MoveTo(Vec2::zero()), Close]).get_distance(&vec![], Vec2::zero()), 99999.0);
+        }
+
+        #[test]
+        fn test_polygon() {
+            // Same as the `Triangle` test, in both windings
+            for points in &[
+                [Vec2::zero(), Vec2::new(4.0, 0.0), Vec2::new(0.0, 3.0)],
+                [Vec2::zero(), Vec2::new(0.0, 3.0), Vec2::new(4.0, 0.0)],
+            ] {
+                let polygon = Polygon::new(points.to_vec());
+
+                assert_distance(&polygon, Vec2::new(1.0, 1.0), -1.0);
+                assert_distance(&polygon, Vec2::new(-1.0, 0.0), 1.0);
+                assert_distance(&polygon, Vec2::new(4.0, 3.0), 12.0 / 5.0);
+                assert_distance(&polygon, Vec2::new(-3.0, -4.0), 5.0);
+            }
+
+            // Self-intersecting star, the center has winding 2
+            let star = (0..5)
+                .map(|i| {
+                    let (sin, cos) = (i as f32 * 4.0 * PI / 5.0).sin_cos();
+                    Vec2::new(sin, cos)
+                })
+                .collect::<Vec<_>>();
+            let polygon = Polygon::new(star);
+
+            assert!(polygon.get_distance(&vec![], Vec2::zero()) < 0.0);
+            assert!(polygon.get_distance(&vec![], Vec2::new(0.0, 0.9)) < 0.0);
+            assert!(polygon.get_distance(&vec![], Vec2::new(0.0, -0.9)) > 0.0);
+
+            assert_eq!(Polygon::new(vec![]).get_distance(&vec![], Vec2::zero()), 99999.0);
+        }
+
+        #[test]
+        fn test_polygon_grid() {
+            // Circle with many vertices, the grid search must match a linear scan
+            let count = 2000;
+            let points = (0..count)
+                .map(|i| {
+                    let angle = i as f32 / count as f32 * 2.0 * PI;
+                    let radius = 100.0 + (angle * 7.0).sin() * 20.0;
+                    Vec2::new(angle.cos(), angle.sin()) * radius
+                })
+                .collect::<Vec<_>>();
+            let polygon = Polygon::new(points.clone());
+
+            for j in -30..30 {
+                for i in -30..30 {
+                    let point = Vec2::new(i as f32 * 5.3, j as f32 * 4.9);
+                    let expected = (0..count)
+                        .map(|k| get_segment_distance(point, points[k], points[(k + 1) % count]))
+                        .fold(f32::INFINITY, f32::min);
+                    let distance = polygon.get_distance(&vec![], point);
+
+                    let crossings = (0..count)
+                        .filter(|&k| {
+                            let (a, b) = (points[k], points[(k + 1) % count]);
+                            (a.y > point.y) != (b.y > point.y) && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x)
+                        })
+                        .count();
+
+                    assert_eq!(distance.abs(), expected);
+                    assert_eq!(distance < 0.0, crossings % 2 == 1);
+                }
+            }
+        }
+
+        #[test]
+        fn test_polyline() {
+            let polyline = Polyline::new(vec![Vec2::zero(), Vec2::new(2.0, 0.0), Vec2::new(2.0, 2.0)], 0.5);
+
+            assert_distance(&polyline, Vec2::new(1.0, 0.0), -0.25);
+            assert_distance(&polyline, Vec2::new(1.0, 1.0), 0.75);
+            assert_distance(&polyline, Vec2::new(-1.0, 0.0), 0.75);
+            // Open, the missing closing segment doesn't count
+            assert_distance(&polyline, Vec2::new(0.5, 1.5), 1.25);
+
+            assert_distance(&Polyline::new(vec![Vec2::new(1.0, 1.0)], 2.0), Vec2::new(1.0, 3.0), 1.0);
+        }
+
+        #[test]
+        fn test_text_lines() {
+            let font = get_font();
+            let text = Text::new(String::from("A\nB"), 32.0, Arc::clone(&font));
+            let lines = text.get_lines();
+
+            assert_eq!(lines, vec![(String::from("A"), true), (String::from("B"), true)]);
+            assert_eq!(text.bboxes.len(), 2);
+            assert_eq!(text.bboxes[1].pos.y - text.bboxes[0].pos.y, font.line_height + font.get_char('B').y_offset - font.get_char('A').y_offset);
+        }
+
+        #[test]
+        fn test_text_wrap_and_align() {
+            let font = get_font();
+            let word_width = Text::new(String::from("hello"), 32.0, Arc::clone(&font)).get_line_width("hello");
+            let layout = TextLayout {
+                align: TextAlign::Right,
+                max_width: Some(word_width * 1.5),
+                line_spacing: 2.0,
+            };
+            let text = Text::with_layout(String::from("hello hi"), 32.0, Arc::clone(&font), layout);
+
+            assert_eq!(text.get_lines(), vec![(String::from("hello"), false), (String::from("hi"), true)]);
+
+            // Both lines end at `max_width`
+            assert_eq!(text.bboxes.len(), 7);
+            assert_eq!(text.bboxes[0].pos.x, word_width * 0.5);
+            assert_eq!(text.bboxes[5].pos.x + text.get_line_width("hi"), word_width * 1.5);
+            assert_eq!(text.bboxes[6].pos.y - font.get_char('i').y_offset, font.line_height * 2.0);
+        }
+
+        #[test]
+        fn test_text_wrap_indentation() {
+            let font = get_font();
+            let word_width = Text::new(String::from("hello"), 32.0, Arc::clone(&font)).get_line_width("hello");
+            let layout = TextLayout {
+                align: TextAlign::Right,
+                max_width: Some(word_width * 1.5),
+                line_spacing: 1.0,
+            };
+
+            // Leading spaces are kept, the ones at the break are dropped
+            let text = Text::with_layout(String::from("  hi hello   hi\n  hi"), 32.0, Arc::clone(&font), layout);
+
+            assert_eq!(
+                text.get_lines(),
+                vec![(String::from("  hi"), false), (String::from("hello"), false), (String::from("hi"), true), (String::from("  hi"), true)],
+            );
+
+            // Right aligned, the indentation still counts
+            assert_eq!(text.bboxes[0].pos.x + text.get_line_width("hi"), word_width * 1.5);
+        }
+
+        #[test]
+        fn test_text_size() {
+            let font = get_font();
+            let arena = vec![];
+            let text = Text::new(String::from("o"), font.size, Arc::clone(&font));
+            let double_text = Text::new(String::from("o"), font.size * 2.0, Arc::clone(&font));
+            let bbox = &text.bboxes[0];
+            let point = bbox.pos + bbox.size * 0.5;
+            let point = Vec2::new(point.x, -point.y);
+
+            let distance = text.get_distance(&arena, point);
+            let double_distance = double_text.get_distance(&arena, point * 2.0);
+
+            assert!(distance > 0.0);
+            assert!((double_distance - distance * 2.0).abs() < 0.0001);
+        }
+
+        #[test]
+        fn test_text_grid() {
+            let font = get_font();
+            let arena = vec![];
+            let layout = TextLayout { align: TextAlign::Center, max_width: Some(300.0), line_spacing: 0.8 };
+            let text = Text::with_layout(String::from("The quick brown fox\njumps over the lazy dog"), 32.0, font, layout);
+
+            // Grid search finds the same distance as a linear scan
+            for j in -40..120 {
+                for i in -40..170 {
+                    let point = Vec2::new(i as f32 * 2.1, j as f32 * 1.7);
+                    let expected = text.bboxes.iter()
+                        .map(|bbox| text.get_glyph_distance(point, bbox))
+                        .fold(f32::INFINITY, f32::min);
+
+                    assert_eq!(text.get_distance(&arena, Vec2::new(point.x, -point.y)), expected);
+                }
+            }
+        }
+
+        #[test]
+        fn test_text_outside_distance() {
+            let font = get_font();
+            let arena = vec![];
+            let text = Text::new(String::from("I"), 32.0, font);
+            let bbox = &text.bboxes[0];
+            let left = Vec2::new(bbox.pos.x, -(bbox.pos.y + bbox.size.y * 0.5));
+
+            // Grows with the distance to the glyph instead of jumping to a sentinel
+            for offset in 1..100 {
+                let point = left - Vec2::new(offset as f32, 0.0);
+                let distance = text.get_distance(&arena, point);
+
+                assert!(distance >= offset as f32 && distance <= offset as f32 + text.get_padding());
+            }
+        }
+
+        #[test]
+        fn test_text_lower_bound() {
+            use crate::font::{Char, DistanceField};
+            use std::collections::HashMap;
+
+            // 32x32 atlas with a disc of radius 13, 3px away from the glyph box edges
+            let (center, radius, distance_range) = (Vec2::new(16.0, 16.0), 13.0, 16.0);
+            let page = (0..32 * 32)
+                .flat_map(|index| {
+                    let distance = (Vec2::new((index % 32) as f32, (index / 32) as f32) - center).length() - radius;
+                    let value = (0.5 - distance / distance_range).clamp(0.0, 1.0);
+
+                    vec![0xFF, 0xFF, 0xFF, (value * 255.0).round() as u8]
+                })
+                .collect();
+            let mut chars = HashMap::new();
+
+            chars.insert('o' as u32, Char { code: 'o' as u32, page: 0, width: 32.0, height: 32.0, x: 0.0, x_advance: 32.0, x_offset: 0.0, y: 0.0, y_offset: 0.0 });
+
+            let font = Font {
+                size: 32.0,
+                line_height: 32.0,
+                base: 32.0,
+                scale_width: 32.0,
+                scale_height: 32.0,
+                chars,
+                kernings: HashMap::new(),
+                fallback: 'o' as u32,
+                distance_field: DistanceField::Sdf,
+                distance_range,
+                pages: vec![page],
+            };
+            let text = Text::new(String::from("o"), 32.0, Arc::new(font));
+            let center = text.bboxes[0].pos + center;
+
+            for step in 0..360 {
+                for &length in &[14.0, 18.0, 23.0, 30.0, 50.0] {
+                    let angle = (step as f32).to_radians();
+                    let point = center + Vec2::new(angle.cos(), angle.sin()) * length;
+                    let distance = text.get_distance(&vec![], Vec2::new(point.x, -point.y));
+
+                    // Quantization & bilinear filtering error
+                    assert!(distance <= length - radius + 0.05, "{:?} {} {}", point, distance, length - radius);
+                }
+            }
+        }
+
+        #[test]
+        fn test_vector_text() {
+            use crate::font::VectorGlyph;
+            use std::collections::HashMap;
+
+            // "I" glyph: 100x700 font units box, sitting on the baseline
+            let mut outline = Outline::new();
+            outline.move_to(0.0, 0.0);
+            outline.line_to(100.0, 0.0);
+            outline.line_to(100.0, 700.0);
+            outline.line_to(0.0, 700.0);
+            outline.close();
+
+            let mut glyphs = HashMap::new();
+            glyphs.insert('I' as u32, VectorGlyph { code: 'I' as u32, x_advance: 200.0, outline });
+
+            let font = Arc::new(VectorFont {
+                units_per_em: 1000.0,
+                ascender: 800.0,
+                line_height: 1000.0,
+                glyphs,
+                kernings: HashMap::new(),
+                fallback: 'I' as u32,
+            });
+            let arena = vec![];
+            let text = VectorText::new(String::from("I?\nI"), 10.0, font);
+
+            // Glyphs at x = [0, 1] & [2, 3], y = [-8, -1] and [-18, -11] on the second line
+            assert_eq!(text.glyphs.len(), 3);
+            assert!((text.get_distance(&arena, Vec2::new(0.5, -4.0)) + 0.5).abs() < 0.0001);
+            assert!((text.get_distance(&arena, Vec2::new(1.5, -4.0)) - 0.5).abs() < 0.0001);
+            assert!((text.get_distance(&arena, Vec2::new(-2.0, -4.0)) - 2.0).abs() < 0.0001);
+            assert!((text.get_distance(&arena, Vec2::new(0.5, -12.0)) + 0.5).abs() < 0.0001);
+            assert!((text.get_distance(&arena, Vec2::new(100.0, -4.0)) - 97.0).abs() < 0.001);
+        }
+    }
+}
+
+pub mod operator {
+    use std::f32::consts::FRAC_1_SQRT_2;
+
+    use super::*;
+
+    // Distance combinators, `fuzz`/`radius` is the size of the blend between both shapes.
+    // Chamfer, stairs & round variants come from hg_sdf (http://mercury.sexy/hg_sdf/).
+
+    pub fn union(d1: f32, d2: f32) -> f32 {
+        d1.min(d2)
+    }
+
+    /// `d1` with `d2` cut out of it
+    pub fn subtraction(d1: f32, d2: f32) -> f32 {
+        d1.max(-d2)
+    }
+
+    pub fn intersection(d1: f32, d2: f32) -> f32 {
+        d1.max(d2)
+    }
+
+    pub fn xor(d1: f32, d2: f32) -> f32 {
+        d1.min(d2).max(-d1.max(d2))
+    }
+
+    pub fn smooth_union(d1: f32, d2: f32, fuzz: f32) -> f32 {
+        if fuzz <= 0.0 {
+            return union(d1, d2);
+        }
+
+        let h = (0.5 + 0.5 * (d2 - d1) / fuzz).clamp(0.0, 1.0);
+        lerp(d2, d1, h) - fuzz * h * (1.0 - h)
+    }
+
+    pub fn smooth_subtraction(d1: f32, d2: f32, fuzz: f32) -> f32 {
+        -smooth_union(-d1, d2, fuzz)
+    }
+
+    pub fn smooth_intersection(d1: f32, d2: f32, fuzz: f32) -> f32 {
+        -smooth_union(-d1, -d2, fuzz)
+    }
+
+    pub fn chamfer_union(d1: f32, d2: f32, radius: f32) -> f32 {
+        union(d1, d2).min((d1 - radius + d2) * FRAC_1_SQRT_2)
+    }
+
+    pub fn chamfer_subtraction(d1: f32, d2: f32, radius: f32) -> f32 {
+        chamfer_intersection(d1, -d2, radius)
+    }
+
+    pub fn chamfer_intersection(d1: f32, d2: f32, radius: f32) -> f32 {
+        intersection(d1, d2).max((d1 + radius + d2) * FRAC_1_SQRT_2)
+    }
+
+    /// Union with `steps` stairs in the corner
+    pub fn stairs_union(d1: f32, d2: f32, radius: f32, steps: u32) -> f32 {
+        let step = radius / steps.max(1) as f32;
+        let u = d2 - radius;
+
+        union(d1, d2).min(0.5 * (u + d1 + ((u - d1 + step).rem_euclid(2.0 * step) - step).abs()))
+    }
+
+    pub fn stairs_subtraction(d1: f32, d2: f32, radius: f32, steps: u32) -> f32 {
+        -stairs_union(-d1, d2, radius, steps)
+    }
+
+    pub fn stairs_intersection(d1: f32, d2: f32, radius: f32, steps: u32) -> f32 {
+        -stairs_union(-d1, -d2, radius, steps)
+    }
+
+    /// Union with a quarter circle in the corner, unlike `smooth_union` the shapes are untouched away from it
+    pub fn round_union(d1: f32, d2: f32, radius: f32) -> f32 {
+        let u = Vec2::new((radius - d1).max(0.0), (radius - d2).max(0.0));
+        radius.max(union(d1, d2)) - u.length()
+    }
+
+    pub fn round_subtraction(d1: f32, d2: f32, radius: f32) -> f32 {
+        round_intersection(d1, -d2, radius)
+    }
+
+    pub fn round_intersection(d1: f32, d2: f32, radius: f32) -> f32 {
+        let u = Vec2::new((radius + d1).max(0.0), (radius + d2).max(0.0));
+        (-radius).min(intersection(d1, d2)) + u.length()
+    }
+
+    fn get_distances(arena: &Vec<Object>, sdf_1: usize, sdf_2: usize, point: Vec2) -> (f32, f32) {
+        (arena[sdf_1].get_distance(arena, point), arena[sdf_2].get_distance(arena, point))
+    }
+
+    pub struct OpUnion {
+        pub sdf_1: usize,
+        pub sdf_2: usize,
+    }
+
+    impl SDF for OpUnion {
+        fn get_distance(&self, arena: &Vec<Object>, point: Vec2) -> f32 {
+            let (d1, d2) = get_distances(arena, self.sdf_1, self.sdf_2, point);
+            union(d1, d2)
+        }
+    }
+
+    /// `sdf_1` with `sdf_2` cut out of it
+    pub struct OpSubtraction {
+        pub sdf_1: usize,
+        pub sdf_2: usize,
+    }
+
+    impl SDF for OpSubtraction {
+        fn get_distance(&self, arena: &Vec<Object>, point: Vec2) -> f32 {
+            let (d1, d2) = get_distances(arena, self.sdf_1, self.sdf_2, point);
+            subtraction(d1, d2)
+        }
+    }
+
+    pub struct OpIntersection {
+        pub sdf_1: usize,
+        pub sdf_2: usize,
+    }
+
+    impl SDF for OpIntersection {
+        fn get_distance(&self, arena: &Vec<Object>, point: Vec2) -> f32 {
+            let (d1, d2) = get_distances(arena, self.sdf_1, self.sdf_2, point);
+            intersection(d1, d2)
+        }
+    }
+
+    /// Areas covered by only one of the shapes
+    pub struct OpXor {
+        pub sdf_1: usize,
+        pub sdf_2: usize,
+    }
+
+    impl SDF for OpXor {
+        fn get_distance(&self, arena: &Vec<Object>, point: Vec2) -> f32 {
+            let (d1, d2) = get_distances(arena, self.sdf_1, self.sdf_2, point);
+            xor(d1, d2)
+        }
+    }
+
+    pub struct OpSmoothUnion {
+        pub sdf_1: usize,
+        pub sdf_2: usize,
+        pub fuzz: f32,
+    }
+
+    impl SDF for OpSmoothUnion {
+        fn get_distance(&self, arena: &Vec<Object>, point: Vec2) -> f32 {
+            let (d1, d2) = get_distances(arena, self.sdf_1, self.sdf_2, point);
+            smooth_union(d1, d2, self.fuzz)
+        }
+    }
+
+    pub struct OpSmoothSubtraction {
+        pub sdf_1: usize,
+        pub sdf_2: usize,
+        pub fuzz: f32,
+    }
+
+    impl SDF for OpSmoothSubtraction {
+        fn get_distance(&self, arena: &Vec<Object>, point: Vec2) -> f32 {
+            let (d1, d2) = get_distances(arena, self.sdf_1, self.sdf_2, point);
+            smooth_subtraction(d1, d2, self.fuzz)
+        }
+    }
+
+    pub struct OpSmoothIntersection {
+        pub sdf_1: usize,
+        pub sdf_2: usize,
+        pub fuzz: f32,
+    }
+
+    impl SDF for OpSmoothIntersection {
+        fn get_distance(&self, arena: &Vec<Object>, point: Vec2) -> f32 {
+            let (d1, d2) = get_distances(arena, self.sdf_1, self.sdf_2, point);
+            smooth_intersection(d1, d2, self.fuzz)
+        }
+    }
+
+    pub struct OpChamferUnion {
+        pub sdf_1: usize,
+        pub sdf_2: usize,
+        pub radius: f32,
+    }
+
+    impl SDF for OpChamferUnion {
+        fn get_distance(&self, arena: &Vec<Object>, point: Vec2) -> f32 {
+            let (d1, d2) = get_distances(arena, self.sdf_1, self.sdf_2, point);
+            chamfer_union(d1, d2, self.radius)
+        }
+    }
+
+    pub struct OpChamferSubtraction {
+        pub sdf_1: usize,
+        pub sdf_2: usize,
+        pub radius: f32,
+    }
+
+    impl SDF for OpChamferSubtraction {
+        fn get_distance(&self, arena: &Vec<Object>, point: Vec2) -> f32 {
+            let (d1, d2) = get_distances(arena, self.sdf_1, self.sdf_2, point);
+            chamfer_subtraction(d1, d2, self.radius)
+        }
+    }
+
+    pub struct OpChamferIntersection {
+        pub sdf_1: usize,
+        pub sdf_2: usize,
+        pub radius: f32,
+    }
+
+    impl SDF for OpChamferIntersection {
+        fn get_distance(&self, arena: &Vec<Object>, point: Vec2) -> f32 {
+            let (d1, d2) = get_distances(arena, self.sdf_1, self.sdf_2, point);
+            chamfer_intersection(d1, d2, self.radius)
+        }
+    }
+
+    pub struct OpStairsUnion {
+        pub sdf_1: usize,
+        pub sdf_2: usize,
+        pub radius: f32,
+        pub steps: u32,
+    }
+
+    impl SDF for OpStairsUnion {
+        fn get_distance(&self, arena: &Vec<Object>, point: Vec2) -> f32 {
+            let (d1, d2) = get_distances(arena, self.sdf_1, self.sdf_2, point);
+            stairs_union(d1, d2, self.radius, self.steps)
+        }
+    }
+
+    pub struct OpStairsSubtraction {
+        pub sdf_1: usize,
+        pub sdf_2: usize,
+        pub radius: f32,
+        pub steps: u32,
+    }
+
+    impl SDF for OpStairsSubtraction {
+        fn get_distance(&self, arena: &Vec<Object>, point: Vec2) -> f32 {
+            let (d1, d2) = get_distances(arena, self.sdf_1, self.sdf_2, point);
+            stairs_subtraction(d1, d2, self.radius, self.steps)
+        }
+    }
+
+    pub struct OpStairsIntersection {
+        pub sdf_1: usize,
+        pub sdf_2: usize,
+        pub radius: f32,
+        pub steps: u32,
+    }
+
+    impl SDF for OpStairsIntersection {
+        fn get_distance(&self, arena: &Vec<Object>, point: Vec2) -> f32 {
+            let (d1, d2) = get_distances(arena, self.sdf_1, self.sdf_2, point);
+            stairs_intersection(d1, d2, self.radius, self.steps)
+        }
+    }
+
+    pub struct OpRoundUnion {
+        pub sdf_1: usize,
+        pub sdf_2: usize,
+        pub radius: f32,
+    }
+
+    impl SDF for OpRoundUnion {
+        fn get_distance(&self, arena: &Vec<Object>, point: Vec2) -> f32 {
+            let (d1, d2) = get_distances(arena, self.sdf_1, self.sdf_2, point);
+            round_union(d1, d2, self.radius)
+        }
+    }
+
+    pub struct OpRoundSubtraction {
+        pub sdf_1: usize,
+        pub sdf_2: usize,
+        pub radius: f32,
+    }
+
+    impl SDF for OpRoundSubtraction {
+        fn get_distance(&self, arena: &Vec<Object>, point: Vec2) -> f32 {
+            let (d1, d2) = get_distances(arena, self.sdf_1, self.sdf_2, point);
+            round_subtraction(d1, d2, self.radius)
+        }
+    }
+
+    pub struct OpRoundIntersection {
+        pub sdf_1: usize,
+        pub sdf_2: usize,
+        pub radius: f32,
+    }
+
+    impl SDF for OpRoundIntersection {
+        fn get_distance(&self, arena: &Vec<Object>, point: Vec2) -> f32 {
+            let (d1, d2) = get_distances(arena, self.sdf_1, self.sdf_2, point);
+            round_intersection(d1, d2, self.radius)
+        }
+    }
+
+    /// Ids of the objects whose `parent_id` is `parent_id`
+    pub fn get_children(arena: &Vec<Object>, parent_id: usize) -> Vec<usize> {
+        arena.iter().enumerate().filter(|(_, object)| object.parent_id == Some(parent_id)).map(|(id, _)| id).collect()
+    }
+
+    /// Circle containing all the children of a group. Points farther than `margin` from it get the distance
+    /// to the circle (a lower bound of the real distance) without evaluating the children.
+    #[derive(Debug, Clone, Copy)]
+    pub struct Bounds {
+        pub center: Vec2,
+        pub radius: f32,
+        pub margin: f32,
+    }
+
+    impl Bounds {
+        fn get_distance(bounds: &Option<Bounds>, point: Vec2) -> Option<f32> {
+            let bounds = bounds.as_ref()?;
+            let distance = (point - bounds.center).length() - bounds.radius;
+
+            if distance > bounds.margin { Some(distance) } else { None }
         }
+    }
 
-        self.sdf.get_distance(arena, point) * self.transform.scale
+    /// Union of any number of objects, e.g. the children of a group (see `OpGroupUnion::from_parent`)
+    pub struct OpGroupUnion {
+        pub children: Vec<usize>,
+        pub bounds: Option<Bounds>,
+    }
 
-        // Matrix
-        // let point = self.transform.to_matrix().inverse() * point.extend(1.0);
-        // self.sdf.get_distance(arena, point.xy() / self.transform.scale) * self.transform.scale
+    impl OpGroupUnion {
+        pub fn from_parent(arena: &Vec<Object>, parent_id: usize) -> OpGroupUnion {
+            OpGroupUnion { children: get_children(arena, parent_id), bounds: None }
+        }
     }
-}
 
-pub mod primitive {
-    use std::sync::Arc;
+    impl SDF for OpGroupUnion {
+        fn get_distance(&self, arena: &Vec<Object>, point: Vec2) -> f32 {
+            if let Some(distance) = Bounds::get_distance(&self.bounds, point) {
+                return distance;
+            }
 
-    use crate::font::{Char, Font};
+            if self.children.is_empty() {
+                // Empty group
+                return 99999.0;
+            }
 
-    use super::*;
+            self.children.iter().map(|&id| arena[id].get_distance(arena, point)).fold(f32::INFINITY, union)
+        }
+    }
 
-    pub struct Circle {
-        pub radius: f32,
+    pub struct OpGroupIntersection {
+        pub children: Vec<usize>,
+        pub bounds: Option<Bounds>,
     }
 
-    impl SDF for Circle {
-        fn get_distance(&self, _arena: &Vec<Object>, point: Vec2) -> f32 {
-            point.length() - self.radius
+    impl OpGroupIntersection {
+        pub fn from_parent(arena: &Vec<Object>, parent_id: usize) -> OpGroupIntersection {
+            OpGroupIntersection { children: get_children(arena, parent_id), bounds: None }
         }
     }
 
-    pub struct Square {
-        pub size: Vec2,
-    }
+    impl SDF for OpGroupIntersection {
+        fn get_distance(&self, arena: &Vec<Object>, point: Vec2) -> f32 {
+            if let Some(distance) = Bounds::get_distance(&self.bounds, point) {
+                return distance;
+            }
 
-    impl SDF for Square {
-        fn get_distance(&self, _arena: &Vec<Object>, point: Vec2) -> f32 {
-            let d = point.abs() - self.size;
-            let a = Vec2::new(d.x.max(0.0), d.y.max(0.0));
+            if self.children.is_empty() {
+                // Empty group
+                return 99999.0;
+            }
 
-            a.length() + d.x.max(d.y).min(0.0)
+            self.children.iter().map(|&id| arena[id].get_distance(arena, point)).fold(f32::NEG_INFINITY, intersection)
         }
     }
 
-    pub struct Text {
-        bboxes: Vec<BBox>,
-        font: Arc<Font>,
-        size: f32,
-        text: String,
+    /// Smooth union of any number of objects. With two children it matches `OpSmoothUnion`.
+    /// The blend can grow the shape by up to `fuzz / 4`, the bounds have to include it.
+    pub struct OpGroupSmoothUnion {
+        pub children: Vec<usize>,
+        pub bounds: Option<Bounds>,
+        pub fuzz: f32,
     }
 
-    #[derive(Debug)]
-    struct BBox {
-        char: Char,
-        pos: Vec2,
-        size: Vec2,
+    impl OpGroupSmoothUnion {
+        pub fn from_parent(arena: &Vec<Object>, parent_id: usize, fuzz: f32) -> OpGroupSmoothUnion {
+            OpGroupSmoothUnion { children: get_children(arena, parent_id), bounds: None, fuzz }
+        }
     }
 
-    impl BBox {
-        pub fn contains(&self, point: Vec2) -> bool {
-            (point.x >= self.pos.x && point.x <= (self.pos.x + self.size.x)) &&
-            (point.y >= self.pos.y && point.y <= (self.pos.y + self.size.y))
+    impl SDF for OpGroupSmoothUnion {
+        fn get_distance(&self, arena: &Vec<Object>, point: Vec2) -> f32 {
+            if let Some(distance) = Bounds::get_distance(&self.bounds, point) {
+                return distance;
+            }
+
+            let mut distances = self.children.iter().map(|&id| arena[id].get_distance(arena, point));
+
+            let distance = match distances.next() {
+                Some(distance) => distance,
+                // Empty group
+                None => return 99999.0,
+            };
+
+            distances.fold(distance, |d1, d2| smooth_union(d1, d2, self.fuzz))
         }
     }
 
-    impl Text {
-        pub fn new(text: String, size: f32, font: Arc<Font>) -> Text {
-            let mut text = Text { bboxes: vec![], font, size, text };
-            text.generate_bboxes();
-            text
-        }
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::sdf::primitive::{Circle, Square};
 
-        fn get_bboxes(&self, point: Vec2) -> Vec<&BBox> {
-            self.bboxes.iter().filter(|bbox| bbox.contains(point)).collect()
+        fn get_object<'a>(x: f32, sdf: Box<dyn SDF + Sync + Send + 'a>) -> Object<'a> {
+            Object { transform: Transform { x, ..Transform::new() }, distortion: vec![], modifier: vec![], parent_id: None, sdf }
         }
 
-        fn get_char_distance(&self, point: &Vec2, bbox: &BBox) -> f32 {
-            let bbox_point: Vec2 = Vec2::new(point.x, point.y) - bbox.pos; // BBox Space
-            let img_point: Vec2 = bbox_point + Vec2::new(bbox.char.x, bbox.char.y); // Texture Space
+        #[test]
+        fn test_hard_operators() {
+            // Two unit circles overlapping between x=0 and x=1
+            let arena = vec![get_object(0.0, Box::new(Circle { radius: 1.0 })), get_object(1.0, Box::new(Circle { radius: 1.0 }))];
+            let get_distance = |sdf: &dyn SDF, x: f32| sdf.get_distance(&arena, Vec2::new(x, 0.0));
+
+            let union = OpUnion { sdf_1: 0, sdf_2: 1 };
+            assert_eq!(get_distance(&union, 0.5), -0.5);
+            assert_eq!(get_distance(&union, 1.5), -0.5);
+            assert_eq!(get_distance(&union, 3.0), 1.0);
 
-            // Top-left sample position on the buffer
-            let x = img_point.x.floor() as usize;
-            let y = img_point.y.floor() as usize;
-            let width = self.font.scale_width as usize;
+            let subtraction = OpSubtraction { sdf_1: 0, sdf_2: 1 };
+            assert_eq!(get_distance(&subtraction, -0.5), -0.5);
+            assert_eq!(get_distance(&subtraction, 0.5), 0.5);
 
-            // Get the value for the 4 distance samples
-            let a = self.font.image[(y       * width) + x] as f32;     // Top-left
-            let b = self.font.image[(y       * width) + x + 1] as f32; // Top-right
-            let c = self.font.image[((y + 1) * width) + x] as f32;     // Bot-left
-            let d = self.font.image[((y + 1) * width) + x + 1] as f32; // Bot-right
+            let intersection = OpIntersection { sdf_1: 0, sdf_2: 1 };
+            assert_eq!(get_distance(&intersection, 0.5), -0.5);
+            assert_eq!(get_distance(&intersection, -0.5), 0.5);
+
+            let xor = OpXor { sdf_1: 0, sdf_2: 1 };
+            assert_eq!(get_distance(&xor, -0.5), -0.5);
+            assert_eq!(get_distance(&xor, 0.5), 0.5);
+            assert_eq!(get_distance(&xor, 1.5), -0.5);
+            assert_eq!(get_distance(&xor, 3.0), 1.0);
+        }
 
-            // Calculate the weights for each sample
-            let a_weight = ((x + 1) as f32 - img_point.x) * ((y + 1) as f32 - img_point.y);
-            let b_weight = (img_point.x - (x as f32)) * ((y + 1) as f32 - img_point.y);
-            let c_weight = ((x + 1) as f32 - img_point.x) * (img_point.y - y as f32);
-            let d_weight = (img_point.x - (x as f32)) * (img_point.y - y as f32);
+        #[test]
+        fn test_blended_operators() {
+            // Far from the corner all the variants match the hard operators
+            for &(d1, d2) in &[(-5.0, 3.0), (6.0, -4.0), (-8.0, 6.0)] {
+                assert_eq!(smooth_union(d1, d2, 1.0), union(d1, d2));
+                assert_eq!(chamfer_union(d1, d2, 1.0), union(d1, d2));
+                assert_eq!(stairs_union(d1, d2, 1.0, 4), union(d1, d2));
+                assert_eq!(round_union(d1, d2, 1.0), union(d1, d2));
+                assert_eq!(smooth_intersection(d1, d2, 1.0), intersection(d1, d2));
+                assert_eq!(chamfer_intersection(d1, d2, 1.0), intersection(d1, d2));
+                assert_eq!(stairs_intersection(d1, d2, 1.0, 4), intersection(d1, d2));
+                assert_eq!(round_intersection(d1, d2, 1.0), intersection(d1, d2));
+                assert_eq!(smooth_subtraction(d1, -d2, 1.0), subtraction(d1, -d2));
+                assert_eq!(chamfer_subtraction(d1, -d2, 1.0), subtraction(d1, -d2));
+                assert_eq!(stairs_subtraction(d1, -d2, 1.0, 4), subtraction(d1, -d2));
+                assert_eq!(round_subtraction(d1, -d2, 1.0), subtraction(d1, -d2));
+            }
 
-            let value = (a * a_weight) + (b * b_weight) + (c * c_weight) + (d * d_weight); // bilinear-interpolation
-            let value = (255.0 - value) / 255.0; // Map [0.0, 1.0]
+            // In the corner unions add material and intersections remove it
+            assert_eq!(smooth_union(0.0, 0.0, 1.0), -0.25);
+            assert_eq!(chamfer_union(0.0, 0.0, 1.0), -FRAC_1_SQRT_2);
+            assert_eq!(round_union(0.0, 0.0, 1.0), 1.0 - 2.0f32.sqrt());
+            assert!(stairs_union(0.0, 0.0, 1.0, 4) < 0.0);
+            assert_eq!(smooth_intersection(0.0, 0.0, 1.0), 0.25);
+            assert_eq!(chamfer_intersection(0.0, 0.0, 1.0), FRAC_1_SQRT_2);
+            assert_eq!(round_intersection(0.0, 0.0, 1.0), 2.0f32.sqrt() - 1.0);
+            assert!(stairs_intersection(0.0, 0.0, 1.0, 4) > 0.0);
 
-            // Map to [-8.0, 8.0] range, font are generated with 8px padding (16px SDF gradient)
-            // 0.0 is the font curve boundary
-            (value * 16.0) - 8.0
+            // Zero fuzz is the hard union
+            assert_eq!(smooth_union(0.5, 0.2, 0.0), 0.2);
         }
 
-        fn get_initial_cursor_position(&self) -> f32 {
-            let first_char = self.text.chars().next();
+        #[test]
+        fn test_group_operators() {
+            let mut arena = vec![get_object(0.0, Box::new(Circle { radius: 1.0 }))];
 
-            match first_char {
-                Some(char) => -self.font.get_char(char).x_offset,
-                None => 0.0,
+            for &x in &[-2.0, 0.0, 3.0] {
+                let mut object = get_object(x, Box::new(Circle { radius: 1.0 }));
+                object.parent_id = Some(0);
+                arena.push(object);
             }
+
+            let union = OpGroupUnion::from_parent(&arena, 0);
+            let intersection = OpGroupIntersection::from_parent(&arena, 0);
+            let smooth = OpGroupSmoothUnion::from_parent(&arena, 0, 0.5);
+
+            assert_eq!(union.children, vec![1, 2, 3]);
+            assert_eq!(union.get_distance(&arena, Vec2::new(2.5, 0.0)), -0.5);
+            assert_eq!(union.get_distance(&arena, Vec2::new(6.0, 0.0)), 2.0);
+            assert_eq!(intersection.get_distance(&arena, Vec2::new(0.0, 0.0)), 2.0);
+            assert_eq!(OpGroupIntersection { children: vec![1, 2], bounds: None }.get_distance(&arena, Vec2::new(-1.0, 0.0)), 0.0);
+
+            // Same as the binary smooth union, regardless of the order
+            let pair = OpSmoothUnion { sdf_1: 2, sdf_2: 3, fuzz: 1.5 };
+            let reversed = OpGroupSmoothUnion { children: vec![3, 2], bounds: None, fuzz: 1.5 };
+            let point = Vec2::new(1.5, 0.2);
+
+            assert!((reversed.get_distance(&arena, point) - pair.get_distance(&arena, point)).abs() < 1e-6);
+            assert!(smooth.get_distance(&arena, Vec2::new(-1.0, 0.0)) < 0.0);
+            assert_eq!(smooth.get_distance(&arena, Vec2::new(6.0, 0.0)), 2.0);
+            assert_eq!(OpGroupSmoothUnion { children: vec![], bounds: None, fuzz: 1.0 }.get_distance(&arena, point), 99999.0);
+            assert_eq!(OpGroupUnion { children: vec![], bounds: None }.get_distance(&arena, point), 99999.0);
+            assert_eq!(OpGroupIntersection { children: vec![], bounds: None }.get_distance(&arena, point), 99999.0);
         }
 
-        fn generate_bboxes(&mut self) {
-            let mut cursor = self.get_initial_cursor_position();
-
-            for letter in self.text.chars() {
-                let char = self.font.get_char(letter);
-                let x_advance = char.x_advance;
-                
-                self.bboxes.push(BBox {
-                    pos: Vec2::new(cursor + char.x_offset, char.y_offset),
-                    size: Vec2::new(char.width, char.height),
-                    char: char,
-                });
+        #[test]
+        fn test_group_bounds() {
+            let mut arena = vec![get_object(0.0, Box::new(Circle { radius: 1.0 }))];
 
-                cursor += x_advance;
+            for &x in &[-2.0, 0.0, 3.0] {
+                let mut object = get_object(x, Box::new(Circle { radius: 1.0 }));
+                object.parent_id = Some(0);
+                arena.push(object);
             }
-        }
-    }
 
-    impl SDF for Text {
-        fn get_distance(&self, _arena: &Vec<Object>, point: Vec2) -> f32 {
-            let point = Vec2::new(point.x, -point.y);
-            let bboxes = self.get_bboxes(point);
-            let mut distances = bboxes.iter()
-                .map(|bbox| self.get_char_distance(&point, bbox))
-                .collect::<Vec<f32>>();
-
-            if distances.len() > 0 {
-                // Use the lowest distance
-                distances.sort_by(|a: &f32, b: &f32| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
-                distances[0]
-            } else {
-                99999.0
+            // The children span x=-3..4, fuzz / 4 added to the radius for the smooth union
+            let bounds = Some(Bounds { center: Vec2::new(0.5, 0.0), radius: 3.75, margin: 2.0 });
+            let groups: Vec<(Box<dyn SDF>, Box<dyn SDF>)> = vec![
+                (Box::new(OpGroupUnion { bounds, ..OpGroupUnion::from_parent(&arena, 0) }), Box::new(OpGroupUnion::from_parent(&arena, 0))),
+                (Box::new(OpGroupIntersection { bounds, ..OpGroupIntersection::from_parent(&arena, 0) }), Box::new(OpGroupIntersection::from_parent(&arena, 0))),
+                (Box::new(OpGroupSmoothUnion { bounds, ..OpGroupSmoothUnion::from_parent(&arena, 0, 1.0) }), Box::new(OpGroupSmoothUnion::from_parent(&arena, 0, 1.0))),
+            ];
+
+            for (bounded, group) in &groups {
+                for i in 0..64 {
+                    let angle = i as f32 * std::f32::consts::TAU / 64.0;
+
+                    for &radius in &[0.0, 2.0, 4.5, 5.7, 6.0, 10.0, 100.0] {
+                        let point = Vec2::new(0.5, 0.0) + Vec2::new(angle.cos(), angle.sin()) * radius;
+                        let (bounded_distance, distance) = (bounded.get_distance(&arena, point), group.get_distance(&arena, point));
+
+                        // Lower bound far from the bounds, exact near them
+                        if radius - 3.75 > 2.0 {
+                            assert!(bounded_distance <= distance, "{:?} {} {}", point, bounded_distance, distance);
+                        } else {
+                            assert_eq!(bounded_distance, distance);
+                        }
+                    }
+                }
             }
+
+            // Only the bounds are measured far from them
+            assert_eq!(groups[0].0.get_distance(&arena, Vec2::new(10.5, 0.0)), 6.25);
+            assert_eq!(groups[0].1.get_distance(&arena, Vec2::new(10.5, 0.0)), 6.5);
+        }
+
+        #[test]
+        fn test_blended_structs() {
+            let arena = vec![get_object(0.0, Box::new(Square { size: Vec2::new(2.0, 2.0) })), get_object(2.0, Box::new(Circle { radius: 1.0 }))];
+            let point = Vec2::new(1.5, 1.5);
+            let (d1, d2) = get_distances(&arena, 0, 1, point);
+
+            assert_eq!(OpSmoothSubtraction { sdf_1: 0, sdf_2: 1, fuzz: 0.5 }.get_distance(&arena, point), smooth_subtraction(d1, d2, 0.5));
+            assert_eq!(OpChamferIntersection { sdf_1: 0, sdf_2: 1, radius: 0.5 }.get_distance(&arena, point), chamfer_intersection(d1, d2, 0.5));
+            assert_eq!(OpStairsUnion { sdf_1: 0, sdf_2: 1, radius: 0.5, steps: 3 }.get_distance(&arena, point), stairs_union(d1, d2, 0.5, 3));
+            assert_eq!(OpRoundSubtraction { sdf_1: 0, sdf_2: 1, radius: 0.5 }.get_distance(&arena, point), round_subtraction(d1, d2, 0.5));
         }
     }
 }
 
-pub mod operator {
+pub mod color {
+    use std::f32::consts::PI;
+    use std::sync::Arc;
+
+    use crate::color::Color;
+    use crate::image::{Filter, Image, Wrap};
+    use crate::noise::*;
+
     use super::*;
 
-    pub struct OpSmoothUnion {
-        pub sdf_1: usize,
-        pub sdf_2: usize,
-        pub fuzz: f32,
+    /// What a color can depend on when shading a pixel of an object.
+    /// The object is placed in the world through its parents, see `Object::get_parent_point`.
+    pub struct ShadingContext<'a> {
+        /// Canvas point
+        pub point: Vec2,
+        /// Point in the object's local space, after its parents & its own transform & distortions
+        pub local_point: Vec2,
+        /// World distance
+        pub distance: f32,
+        pub time: f32,
+        object: &'a Object<'a>,
+        arena: &'a Vec<Object<'a>>,
     }
 
-    impl SDF for OpSmoothUnion {
-        fn get_distance(&self, arena: &Vec<Object>, point: Vec2) -> f32 {
-            let distance_1 = arena[self.sdf_1].get_distance(arena, point);
-            let distance_2 = arena[self.sdf_2].get_distance(arena, point);
+    impl<'a> ShadingContext<'a> {
+        pub fn new(arena: &'a Vec<Object<'a>>, id: usize, point: Vec2, time: f32) -> ShadingContext<'a> {
+            let object = &arena[id];
+            let (parent_point, scale) = object.get_parent_point(arena, point);
+
+            ShadingContext {
+                point,
+                local_point: object.get_local_point(parent_point),
+                distance: object.get_distance(arena, parent_point) * scale,
+                time,
+                object,
+                arena,
+            }
+        }
+
+        fn get_world_distance(&self, point: Vec2) -> f32 {
+            let (point, scale) = self.object.get_parent_point(self.arena, point);
+
+            self.object.get_distance(self.arena, point) * scale
+        }
+
+        fn get_world_local_point(&self, point: Vec2) -> Vec2 {
+            self.object.get_local_point(self.object.get_parent_point(self.arena, point).0)
+        }
+
+        /// Distance gradient at the canvas point. It's computed on demand as it
+        /// takes four extra distance evaluations.
+        pub fn get_gradient(&self) -> Vec2 {
+            let epsilon = 0.5;
+            let dx = self.get_world_distance(self.point + Vec2::new(epsilon, 0.0)) - self.get_world_distance(self.point - Vec2::new(epsilon, 0.0));
+            let dy = self.get_world_distance(self.point + Vec2::new(0.0, epsilon)) - self.get_world_distance(self.point - Vec2::new(0.0, epsilon));
+
+            Vec2::new(dx, dy) / (2.0 * epsilon)
+        }
+
+        /// Surface normal, pointing outwards
+        pub fn get_normal(&self) -> Vec2 {
+            let gradient = self.get_gradient();
+            let length = gradient.length();
+
+            if length > f32::EPSILON { gradient / length } else { Vec2::zero() }
+        }
+
+        /// Size of a canvas pixel in the object's local space, used to anti-alias patterns
+        pub fn get_footprint(&self) -> f32 {
+            let dx = self.get_world_local_point(self.point + Vec2::new(1.0, 0.0)) - self.local_point;
+            let dy = self.get_world_local_point(self.point + Vec2::new(0.0, 1.0)) - self.local_point;
 
-            let h = (0.5 + 0.5 * (distance_2 - distance_1) / self.fuzz).clamp(0.0, 1.0);
-            return lerp(distance_2, distance_1, h) - self.fuzz * h * (1.0 - h);
+            dx.length().max(dy.length()).max(f32::EPSILON)
         }
     }
-}
 
-pub mod color {
-    use crate::color::Color;
+    /// Coverage of a pattern given its signed distance, with a one pixel wide transition
+    fn get_coverage(distance: f32, footprint: f32) -> f32 {
+        (0.5 - distance / footprint).clamp(0.0, 1.0)
+    }
+
+    /// Signed distance to bands of `width` centered every `period` along `x`
+    fn get_band_distance(x: f32, period: f32, width: f32) -> f32 {
+        ((x + period / 2.0).rem_euclid(period) - period / 2.0).abs() - width / 2.0
+    }
+
+    /// Coordinates of the point in axes rotated clockwise by `angle` degrees
+    fn rotate(point: Vec2, angle: f32) -> Vec2 {
+        let (sin, cos) = angle.to_radians().sin_cos();
+
+        Vec2::new(point.x * cos - point.y * sin, point.x * sin + point.y * cos)
+    }
 
     pub trait SDFColor {
-        fn get_color(&self, distance: f32) -> Color;
+        fn get_color(&self, context: &ShadingContext) -> Color;
+    }
+
+    /// How the gradient behaves outside the range of its stops
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum Spread {
+        /// Extend the first & last colors
+        Pad,
+        /// Start again from the first stop
+        Repeat,
+        /// Go back and forth between the first & last stops
+        Reflect,
+    }
+
+    /// Easing between two consecutive stops
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum Profile {
+        Linear,
+        Smooth,
+        EaseIn,
+        EaseOut,
+        /// Hard bands, each stop color is kept until the next stop
+        Step,
+    }
+
+    impl Profile {
+        pub fn apply(&self, t: f32) -> f32 {
+            let t = t.clamp(0.0, 1.0);
+
+            match self {
+                Profile::Linear => t,
+                Profile::Smooth => smoothstep(0.0, 1.0, t),
+                Profile::EaseIn => t * t,
+                Profile::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+                Profile::Step => if t < 1.0 { 0.0 } else { 1.0 },
+            }
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct ColorStop {
+        pub offset: f32,
+        pub color: Color,
+    }
+
+    impl ColorStop {
+        pub fn new(offset: f32, color: Color) -> ColorStop {
+            ColorStop { offset, color }
+        }
+    }
+
+    /// Multi-stop color ramp. Stops must be sorted by `offset`, for `Repeat` & `Reflect`
+    /// the period is the range between the first & last stop, like CSS repeating gradients.
+    #[derive(Debug, Clone)]
+    pub struct Gradient {
+        pub stops: Vec<ColorStop>,
+        pub spread: Spread,
+        pub profile: Profile,
+    }
+
+    impl Gradient {
+        pub fn get_color(&self, t: f32) -> Color {
+            let (first, last) = match (self.stops.first(), self.stops.last()) {
+                (Some(first), Some(last)) => (first, last),
+                _ => return Color::new(0.0, 0.0, 0.0, 0.0),
+            };
+            let period = last.offset - first.offset;
+            let t = if period > 0.0 {
+                match self.spread {
+                    Spread::Pad => t,
+                    Spread::Repeat => first.offset + (t - first.offset).rem_euclid(period),
+                    Spread::Reflect => {
+                        let t = (t - first.offset).rem_euclid(2.0 * period);
+                        first.offset + if t > period { 2.0 * period - t } else { t }
+                    }
+                }
+            } else {
+                t
+            };
+
+            if t <= first.offset {
+                return first.color.clone();
+            }
+
+            for stops in self.stops.windows(2) {
+                let (from, to) = (&stops[0], &stops[1]);
+
+                if t < to.offset {
+                    let t = (t - from.offset) / (to.offset - from.offset);
+                    return from.color.lerp(&to.color, self.profile.apply(t));
+                }
+            }
+
+            last.color.clone()
+        }
     }
 
     pub enum Fill {
-        Solid(Color)
-    //     - solid
-    //         - color
-    //     - linear gradient
-    //     - radial gradient
-    //     - repeating gradient
-    //         - is_repeating
-    //         - size
-    //         - profile
+        Solid(Color),
+        /// Ramp along the `start` → `end` axis
+        LinearGradient { start: Vec2, end: Vec2, gradient: Gradient },
+        /// Ramp from the `center` (offset `0.0`) to the `radius` (offset `1.0`)
+        RadialGradient { center: Vec2, radius: f32, gradient: Gradient },
+        /// Ramp sweeping clockwise around the `center`, starting at `angle` degrees from the top
+        ConicGradient { center: Vec2, angle: f32, gradient: Gradient },
+        /// Ramp over the signed distance, from `start` (offset `0.0`) to `end` (offset `1.0`),
+        /// e.g. glows & shadow falloffs, or contour bands with `Spread::Repeat`
+        DistanceGradient { start: f32, end: f32, gradient: Gradient },
+        /// Image centered at `center`, stretched to `size` (the size of a single tile)
+        Image { image: Arc<Image>, center: Vec2, size: Vec2, wrap: Wrap, filter: Filter },
+        /// Alternating bands of `width`, `angle` in degrees (`0.0` are vertical stripes)
+        Stripes { angle: f32, width: f32, color: Color, background: Color },
+        Checker { size: f32, color: Color, background: Color },
+        /// Dots of `radius` in a grid of `spacing`, one is centered at the origin
+        Dots { spacing: f32, radius: f32, color: Color, background: Color },
+        /// Two sets of perpendicular lines, `angle` in degrees
+        Hatching { angle: f32, spacing: f32, thickness: f32, color: Color, background: Color },
+        /// Fractal noise mapped through the gradient (noise `-1.0` is offset `0.0`, `1.0` is offset `1.0`).
+        /// `frequency` is the number of noise cells per unit.
+        Noise { noise: NoiseType, frequency: f32, octaves: u32, seed: u32, gradient: Gradient },
     }
 
     impl SDFColor for Fill {
-        fn get_color(&self, _distance: f32) -> Color {
+        fn get_color(&self, context: &ShadingContext) -> Color {
+            let point = context.local_point;
+
             match self {
                 Fill::Solid(color) => color.clone(),
+                Fill::LinearGradient { start, end, gradient } => {
+                    let axis = *end - *start;
+                    let t = (point - *start).dot(axis) / axis.length_squared().max(f32::EPSILON);
+
+                    gradient.get_color(t)
+                }
+                Fill::RadialGradient { center, radius, gradient } => {
+                    gradient.get_color((point - *center).length() / radius.max(f32::EPSILON))
+                }
+                Fill::ConicGradient { center, angle, gradient } => {
+                    let p = point - *center;
+                    let t = (p.x.atan2(p.y) / (2.0 * PI) - angle / 360.0).rem_euclid(1.0);
+
+                    gradient.get_color(t)
+                }
+                Fill::DistanceGradient { start, end, gradient } => {
+                    let range = end - start;
+                    let range = if range.abs() < f32::EPSILON { f32::EPSILON } else { range };
+
+                    gradient.get_color((context.distance - start) / range)
+                }
+                Fill::Image { image, center, size, wrap, filter } => {
+                    // Image rows go top to bottom, `y` goes up
+                    let p = (point - *center) / size.max(Vec2::splat(f32::EPSILON));
+                    let uv = Vec2::new(p.x + 0.5, 0.5 - p.y);
+
+                    image.sample(uv, *wrap, *filter)
+                }
+                Fill::Stripes { angle, width, color, background } => {
+                    let width = width.max(f32::EPSILON);
+                    let distance = get_band_distance(rotate(point, *angle).x, width * 2.0, width);
+
+                    background.lerp(color, get_coverage(distance, context.get_footprint()))
+                }
+                Fill::Checker { size, color, background } => {
+                    let size = size.max(f32::EPSILON);
+                    let cell = (point.x / size).floor() + (point.y / size).floor();
+                    let edge = Vec2::new(
+                        get_band_distance(point.x, size, 0.0),
+                        get_band_distance(point.y, size, 0.0),
+                    ).min_element();
+                    let distance = if cell.rem_euclid(2.0) < 1.0 { -edge } else { edge };
+
+                    background.lerp(color, get_coverage(distance, context.get_footprint()))
+                }
+                Fill::Dots { spacing, radius, color, background } => {
+                    let spacing = spacing.max(f32::EPSILON);
+                    let cell = Vec2::new(
+                        (point.x + spacing / 2.0).rem_euclid(spacing) - spacing / 2.0,
+                        (point.y + spacing / 2.0).rem_euclid(spacing) - spacing / 2.0,
+                    );
+
+                    background.lerp(color, get_coverage(cell.length() - radius, context.get_footprint()))
+                }
+                Fill::Hatching { angle, spacing, thickness, color, background } => {
+                    let spacing = spacing.max(f32::EPSILON);
+                    let p = rotate(point, *angle);
+                    let distance = get_band_distance(p.x, spacing, *thickness).min(get_band_distance(p.y, spacing, *thickness));
+
+                    background.lerp(color, get_coverage(distance, context.get_footprint()))
+                }
+                Fill::Noise { noise, frequency, octaves, seed, gradient } => {
+                    let value = fbm(noise.get_function(), point * *frequency, *seed, *octaves);
+
+                    gradient.get_color(value * 0.5 + 0.5)
+                }
             }
         }
     }
@@ -254,7 +2572,8 @@ pub mod color {
     }
 
     impl SDFColor for LayerColor {
-        fn get_color(&self, distance: f32) -> Color {
+        fn get_color(&self, context: &ShadingContext) -> Color {
+            let distance = context.distance;
             let fuzz = 1.25;
             let has_border = self.border.is_some() && self.border.as_ref().unwrap().size > 0.0;
             let transparent = Color::new(0.0, 0.0, 0.0, 0.0);
@@ -273,14 +2592,14 @@ pub mod color {
                 if distance > (outside_threshold + fuzz) {
                     // Outside
                     match &self.outside {
-                        Some(fill) => fill.get_color(distance),
+                        Some(fill) => fill.get_color(context),
                         None => transparent,
                     }
                 } else if distance > outside_threshold {
                     // Border => Outside transition
                     let t = 1.0 - (distance - outside_threshold) / fuzz;
                     let outside_color = match &self.outside {
-                        Some(fill) => fill.get_color(distance),
+                        Some(fill) => fill.get_color(context),
                         None => transparent.clone()
                     };
     
@@ -288,7 +2607,7 @@ pub mod color {
                         Some(border) => outside_color.blend(&border.color, t),
                         None => {
                             let inside_color = match &self.inside {
-                                Some(fill) => fill.get_color(distance),
+                                Some(fill) => fill.get_color(context),
                                 None => transparent
                             };
     
@@ -302,7 +2621,7 @@ pub mod color {
                     // Inside => Border transition
                     let t = 1.0 - (distance + inside_threshold.abs()).abs() / fuzz;
                     let inside_color = match &self.inside {
-                        Some(fill) => fill.get_color(distance),
+                        Some(fill) => fill.get_color(context),
                         None => transparent.clone()
                     };
     
@@ -310,7 +2629,7 @@ pub mod color {
                         Some(border) => inside_color.blend(&border.color, t),
                         None => {
                             let outside_color = match &self.outside {
-                                Some(fill) => fill.get_color(distance),
+                                Some(fill) => fill.get_color(context),
                                 None => transparent
                             };
     
@@ -320,7 +2639,7 @@ pub mod color {
                 } else {
                     // Inside
                     match &self.inside {
-                        Some(fill) => fill.get_color(distance),
+                        Some(fill) => fill.get_color(context),
                         None => transparent,
                     }
                 }
@@ -333,29 +2652,346 @@ pub mod color {
                 if distance > (outside_threshold + fuzz / 2.0) {
                     // Outside
                     match &self.outside {
-                        Some(fill) => fill.get_color(distance),
+                        Some(fill) => fill.get_color(context),
                         None => transparent,
                     }
                 } else if distance > (outside_threshold - fuzz / 2.0) {
                     let t = ((outside_threshold + fuzz / 2.0) - distance) / fuzz;
                     let outside_color = match &self.outside {
-                        Some(fill) => fill.get_color(distance),
+                        Some(fill) => fill.get_color(context),
                         None => transparent.clone()
                     };
 
                     // Mix Inside & Outside
                     match &self.inside {
-                        Some(fill) => outside_color.blend(&fill.get_color(distance), t),
+                        Some(fill) => outside_color.blend(&fill.get_color(context), t),
                         None => transparent,
                     }
                 } else {
                     // Inside
                     match &self.inside {
-                        Some(fill) => fill.get_color(distance),
+                        Some(fill) => fill.get_color(context),
                         None => transparent,
                     }
                 }
             }
         }
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::distortion::Mirror;
+
+        fn get_arena<'a>() -> Vec<Object<'a>> {
+            vec![Object { transform: Transform::new(), distortion: vec![], modifier: vec![], parent_id: None, sdf: Box::new(primitive::Circle { radius: 100.0 }) }]
+        }
+
+        fn get_context<'a>(arena: &'a Vec<Object<'a>>, distance: f32, local_point: Vec2) -> ShadingContext<'a> {
+            ShadingContext { distance, local_point, ..ShadingContext::new(arena, 0, local_point, 0.0) }
+        }
+
+        fn to_tuple(color: Color) -> (f32, f32, f32, f32) {
+            (color.r, color.g, color.b, color.a)
+        }
+
+        fn assert_color(color: Color, expected: (f32, f32, f32, f32)) {
+            let (r, g, b, a) = expected;
+
+            assert!(
+                (color.r - r).abs() < 0.001 && (color.g - g).abs() < 0.001 && (color.b - b).abs() < 0.001 && (color.a - a).abs() < 0.001,
+                "{:?} != {:?}", color, expected
+            );
+        }
+
+        fn get_gradient(spread: Spread, profile: Profile) -> Gradient {
+            Gradient {
+                stops: vec![
+                    ColorStop::new(0.0, Color::new(0.0, 0.0, 0.0, 1.0)),
+                    ColorStop::new(0.5, Color::new(1.0, 0.0, 0.0, 1.0)),
+                    ColorStop::new(1.0, Color::new(1.0, 1.0, 1.0, 1.0)),
+                ],
+                spread,
+                profile,
+            }
+        }
+
+        #[test]
+        fn test_shading_context() {
+            let arena = vec![Object {
+                transform: Transform { x: 50.0, ..Transform::new() },
+                distortion: vec![],
+                modifier: vec![],
+                parent_id: None,
+                sdf: Box::new(primitive::Circle { radius: 100.0 }),
+            }];
+            let context = ShadingContext::new(&arena, 0, Vec2::new(50.0, 150.0), 2.0);
+
+            assert_eq!(context.point, Vec2::new(50.0, 150.0));
+            assert_eq!(context.local_point, Vec2::new(0.0, 150.0));
+            assert_eq!(context.distance, 50.0);
+            assert_eq!(context.time, 2.0);
+            assert!((context.get_normal() - Vec2::new(0.0, 1.0)).length() < 0.001);
+        }
+
+        #[test]
+        fn test_nested_shading_context() {
+            // Child circle of radius 10 at x=10 in a group at x=100 scaled 2x: in the world it's a circle of radius 20 at x=120
+            let arena = vec![
+                Object {
+                    transform: Transform { x: 100.0, scale: Vec2::splat(2.0), ..Transform::new() },
+                    distortion: vec![],
+                    modifier: vec![],
+                    parent_id: None,
+                    sdf: Box::new(operator::OpGroupUnion { children: vec![1], bounds: None }),
+                },
+                Object {
+                    transform: Transform { x: 10.0, ..Transform::new() },
+                    distortion: vec![],
+                    modifier: vec![],
+                    parent_id: Some(0),
+                    sdf: Box::new(primitive::Circle { radius: 10.0 }),
+                },
+            ];
+            let context = ShadingContext::new(&arena, 1, Vec2::new(150.0, 0.0), 0.0);
+
+            assert_eq!(arena[1].get_parent_point(&arena, Vec2::new(150.0, 0.0)), (Vec2::new(25.0, 0.0), 2.0));
+            assert_eq!(context.local_point, Vec2::new(15.0, 0.0));
+            assert_eq!(context.distance, 10.0);
+            assert!((context.get_gradient() - Vec2::new(1.0, 0.0)).length() < 0.001);
+            assert!((context.get_normal() - Vec2::new(1.0, 0.0)).length() < 0.001);
+            assert!((context.get_footprint() - 0.5).abs() < 0.001);
+
+            // Same as evaluating the group
+            assert_eq!(ShadingContext::new(&arena, 0, Vec2::new(150.0, 0.0), 0.0).distance, 10.0);
+
+            // Mirroring the group: the left copy of the child is at x=80 in the world
+            let mut arena = arena;
+            arena[0].distortion = vec![Box::new(Mirror { angle: 0.0 })];
+            let context = ShadingContext::new(&arena, 1, Vec2::new(50.0, 0.0), 0.0);
+
+            assert_eq!(arena[1].get_parent_point(&arena, Vec2::new(50.0, 0.0)), (Vec2::new(25.0, 0.0), 2.0));
+            assert_eq!(context.local_point, Vec2::new(15.0, 0.0));
+            assert_eq!(context.distance, 10.0);
+            assert!((context.get_gradient() - Vec2::new(-1.0, 0.0)).length() < 0.001);
+            assert_eq!(ShadingContext::new(&arena, 0, Vec2::new(50.0, 0.0), 0.0).distance, 10.0);
+        }
+
+        #[test]
+        fn test_gradient_stops() {
+            let gradient = get_gradient(Spread::Pad, Profile::Linear);
+
+            assert_color(gradient.get_color(0.0), (0.0, 0.0, 0.0, 1.0));
+            assert_color(gradient.get_color(0.25), (0.5, 0.0, 0.0, 1.0));
+            assert_color(gradient.get_color(0.5), (1.0, 0.0, 0.0, 1.0));
+            assert_color(gradient.get_color(0.75), (1.0, 0.5, 0.5, 1.0));
+            assert_color(gradient.get_color(1.0), (1.0, 1.0, 1.0, 1.0));
+
+            // Pad
+            assert_color(gradient.get_color(-1.0), (0.0, 0.0, 0.0, 1.0));
+            assert_color(gradient.get_color(2.0), (1.0, 1.0, 1.0, 1.0));
+
+            // Empty
+            assert_color(Gradient { stops: vec![], ..gradient }.get_color(0.5), (0.0, 0.0, 0.0, 0.0));
+        }
+
+        #[test]
+        fn test_gradient_spread() {
+            let repeat = get_gradient(Spread::Repeat, Profile::Linear);
+            let reflect = get_gradient(Spread::Reflect, Profile::Linear);
+
+            assert_color(repeat.get_color(1.25), (0.5, 0.0, 0.0, 1.0));
+            assert_color(repeat.get_color(-0.25), (1.0, 0.5, 0.5, 1.0));
+            assert_color(reflect.get_color(1.25), (1.0, 0.5, 0.5, 1.0));
+            assert_color(reflect.get_color(-0.25), (0.5, 0.0, 0.0, 1.0));
+            assert_color(reflect.get_color(2.25), (0.5, 0.0, 0.0, 1.0));
+
+            // The period is the range between the first & last stops
+            let bands = Gradient {
+                stops: vec![
+                    ColorStop::new(0.0, Color::new(0.0, 0.0, 0.0, 1.0)),
+                    ColorStop::new(0.2, Color::new(1.0, 1.0, 1.0, 1.0)),
+                ],
+                spread: Spread::Repeat,
+                profile: Profile::Linear,
+            };
+
+            assert_color(bands.get_color(0.1), (0.5, 0.5, 0.5, 1.0));
+            assert_color(bands.get_color(0.7), (0.5, 0.5, 0.5, 1.0));
+        }
+
+        #[test]
+        fn test_gradient_profile() {
+            assert_color(get_gradient(Spread::Pad, Profile::Smooth).get_color(0.125), (0.15625, 0.0, 0.0, 1.0));
+            assert_color(get_gradient(Spread::Pad, Profile::EaseIn).get_color(0.25), (0.25, 0.0, 0.0, 1.0));
+            assert_color(get_gradient(Spread::Pad, Profile::EaseOut).get_color(0.25), (0.75, 0.0, 0.0, 1.0));
+            assert_color(get_gradient(Spread::Pad, Profile::Step).get_color(0.45), (0.0, 0.0, 0.0, 1.0));
+            assert_color(get_gradient(Spread::Pad, Profile::Step).get_color(0.55), (1.0, 0.0, 0.0, 1.0));
+        }
+
+        #[test]
+        fn test_gradient_fills() {
+            let arena = get_arena();
+            let gradient = || get_gradient(Spread::Pad, Profile::Linear);
+            let linear = Fill::LinearGradient { start: Vec2::new(-100.0, 0.0), end: Vec2::new(100.0, 0.0), gradient: gradient() };
+            let radial = Fill::RadialGradient { center: Vec2::new(10.0, 10.0), radius: 100.0, gradient: gradient() };
+            let conic = Fill::ConicGradient { center: Vec2::zero(), angle: 90.0, gradient: gradient() };
+
+            assert_color(linear.get_color(&get_context(&arena, 0.0, Vec2::new(-50.0, 30.0))), (0.5, 0.0, 0.0, 1.0));
+            assert_color(linear.get_color(&get_context(&arena, 0.0, Vec2::new(50.0, -30.0))), (1.0, 0.5, 0.5, 1.0));
+            assert_color(radial.get_color(&get_context(&arena, 0.0, Vec2::new(10.0, 10.0))), (0.0, 0.0, 0.0, 1.0));
+            assert_color(radial.get_color(&get_context(&arena, 0.0, Vec2::new(10.0, 60.0))), (1.0, 0.0, 0.0, 1.0));
+
+            // Starts at the right (90º), clockwise
+            assert_color(conic.get_color(&get_context(&arena, 0.0, Vec2::new(10.0, 0.01))), (1.0, 1.0, 1.0, 1.0));
+            assert_color(conic.get_color(&get_context(&arena, 0.0, Vec2::new(0.0, -10.0))), (0.5, 0.0, 0.0, 1.0));
+            assert_color(conic.get_color(&get_context(&arena, 0.0, Vec2::new(-10.0, 0.0))), (1.0, 0.0, 0.0, 1.0));
+        }
+
+        #[test]
+        fn test_distance_gradient() {
+            let arena = get_arena();
+            let glow = Fill::DistanceGradient { start: 0.0, end: 20.0, gradient: get_gradient(Spread::Pad, Profile::Linear) };
+            let contour = Fill::DistanceGradient { start: 0.0, end: 10.0, gradient: get_gradient(Spread::Repeat, Profile::Smooth) };
+
+            assert_color(glow.get_color(&get_context(&arena, -5.0, Vec2::zero())), (0.0, 0.0, 0.0, 1.0));
+            assert_color(glow.get_color(&get_context(&arena, 5.0, Vec2::zero())), (0.5, 0.0, 0.0, 1.0));
+            assert_color(glow.get_color(&get_context(&arena, 30.0, Vec2::zero())), (1.0, 1.0, 1.0, 1.0));
+
+            // Independent of the point
+            assert_color(glow.get_color(&get_context(&arena, 5.0, Vec2::new(100.0, -50.0))), (0.5, 0.0, 0.0, 1.0));
+
+            // Bands
+            assert_color(contour.get_color(&get_context(&arena, 5.0, Vec2::zero())), (1.0, 0.0, 0.0, 1.0));
+            assert_color(contour.get_color(&get_context(&arena, 25.0, Vec2::zero())), (1.0, 0.0, 0.0, 1.0));
+            assert_color(contour.get_color(&get_context(&arena, -15.0, Vec2::zero())), (1.0, 0.0, 0.0, 1.0));
+        }
+
+        #[test]
+        fn test_pattern_fills() {
+            let arena = get_arena();
+            let (white, black) = (Color::new(1.0, 1.0, 1.0, 1.0), Color::new(0.0, 0.0, 0.0, 1.0));
+            let stripes = Fill::Stripes { angle: 0.0, width: 10.0, color: white.clone(), background: black.clone() };
+            let diagonal = Fill::Stripes { angle: 45.0, width: 10.0, color: white.clone(), background: black.clone() };
+            let checker = Fill::Checker { size: 10.0, color: white.clone(), background: black.clone() };
+            let dots = Fill::Dots { spacing: 20.0, radius: 5.0, color: white.clone(), background: black.clone() };
+            let hatching = Fill::Hatching { angle: 0.0, spacing: 20.0, thickness: 2.0, color: white, background: black };
+            let get_color = |fill: &Fill, x: f32, y: f32| fill.get_color(&ShadingContext::new(&arena, 0, Vec2::new(x, y), 0.0));
+
+            // Vertical stripes, centered at 0.0, 20.0…
+            assert_color(get_color(&stripes, 2.0, 33.0), (1.0, 1.0, 1.0, 1.0));
+            assert_color(get_color(&stripes, 12.0, 33.0), (0.0, 0.0, 0.0, 1.0));
+            assert_color(get_color(&stripes, -18.0, -7.0), (1.0, 1.0, 1.0, 1.0));
+
+            // Anti-aliased edge
+            assert_color(get_color(&stripes, 5.0, 0.0), (0.5, 0.5, 0.5, 1.0));
+            assert_color(get_color(&stripes, 5.25, 0.0), (0.25, 0.25, 0.25, 1.0));
+
+            // Rotated, constant along the diagonal
+            assert_color(get_color(&diagonal, 30.0, 30.0), to_tuple(get_color(&diagonal, 0.0, 0.0)));
+            assert_color(get_color(&diagonal, 10.0, 0.0), (0.0, 0.0, 0.0, 1.0));
+
+            assert_color(get_color(&checker, 5.0, 5.0), (1.0, 1.0, 1.0, 1.0));
+            assert_color(get_color(&checker, 15.0, 5.0), (0.0, 0.0, 0.0, 1.0));
+            assert_color(get_color(&checker, -5.0, 5.0), (0.0, 0.0, 0.0, 1.0));
+            assert_color(get_color(&checker, -5.0, -5.0), (1.0, 1.0, 1.0, 1.0));
+
+            assert_color(get_color(&dots, 41.0, -39.0), (1.0, 1.0, 1.0, 1.0));
+            assert_color(get_color(&dots, 10.0, 10.0), (0.0, 0.0, 0.0, 1.0));
+
+            assert_color(get_color(&hatching, 40.0, 7.0), (1.0, 1.0, 1.0, 1.0));
+            assert_color(get_color(&hatching, 7.0, 60.0), (1.0, 1.0, 1.0, 1.0));
+            assert_color(get_color(&hatching, 7.0, 7.0), (0.0, 0.0, 0.0, 1.0));
+        }
+
+        #[test]
+        fn test_pattern_footprint() {
+            // Scaled 4x, the transition is 4 units wide in local space
+            let arena = vec![Object {
+                transform: Transform { scale: Vec2::splat(4.0), ..Transform::new() },
+                distortion: vec![],
+                modifier: vec![],
+                parent_id: None,
+                sdf: Box::new(primitive::Circle { radius: 100.0 }),
+            }];
+            let stripes = Fill::Stripes { angle: 0.0, width: 10.0, color: Color::new(1.0, 1.0, 1.0, 1.0), background: Color::new(0.0, 0.0, 0.0, 1.0) };
+            let context = ShadingContext::new(&arena, 0, Vec2::new(5.25 * 4.0, 0.0), 0.0);
+
+            assert!((context.get_footprint() - 0.25).abs() < 0.001);
+            assert_color(stripes.get_color(&context), (0.0, 0.0, 0.0, 1.0));
+        }
+
+        #[test]
+        fn test_noise_fill() {
+            let arena = get_arena();
+            let fill = Fill::Noise { noise: NoiseType::Value, frequency: 0.1, octaves: 3, seed: 1, gradient: get_gradient(Spread::Pad, Profile::Linear) };
+
+            for i in 0..100 {
+                let point = Vec2::new(i as f32 * 3.7, i as f32 * -1.3);
+                let color = fill.get_color(&get_context(&arena, 0.0, point));
+                let sample = fbm(value, point * 0.1, 1, 3) * 0.5 + 0.5;
+
+                assert_color(color, to_tuple(get_gradient(Spread::Pad, Profile::Linear).get_color(sample)));
+            }
+        }
+
+        #[test]
+        fn test_image_fill() {
+            let arena = get_arena();
+            // Red, green / blue, white
+            let image = Arc::new(Image::from_rgba(&[255, 0, 0, 255, 0, 255, 0, 255, 0, 0, 255, 255, 255, 255, 255, 255], 2, 2));
+            let fill = Fill::Image { image, center: Vec2::new(100.0, 0.0), size: Vec2::new(200.0, 100.0), wrap: Wrap::Tile, filter: Filter::Nearest };
+
+            assert_color(fill.get_color(&get_context(&arena, 0.0, Vec2::new(50.0, 25.0))), (1.0, 0.0, 0.0, 1.0));
+            assert_color(fill.get_color(&get_context(&arena, 0.0, Vec2::new(150.0, 25.0))), (0.0, 1.0, 0.0, 1.0));
+            assert_color(fill.get_color(&get_context(&arena, 0.0, Vec2::new(50.0, -25.0))), (0.0, 0.0, 1.0, 1.0));
+
+            // Next tile
+            assert_color(fill.get_color(&get_context(&arena, 0.0, Vec2::new(250.0, -25.0))), (0.0, 0.0, 1.0, 1.0));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modifier::Onion;
+
+    #[test]
+    fn test_object_distance() {
+        let wave = || Box::new(Wave { width: 600.0, height: 600.0, x_amplitude: 20.0, x_freq: 25.0, y_amplitude: 10.0, y_freq: 30.0, time: 1.0 });
+        let arena = vec![
+            Object { transform: Transform::new(), distortion: vec![], modifier: vec![], parent_id: None, sdf: Box::new(primitive::Circle { radius: 100.0 }) },
+            Object { transform: Transform::new(), distortion: vec![wave()], modifier: vec![], parent_id: None, sdf: Box::new(primitive::Circle { radius: 100.0 }) },
+            Object { transform: Transform::new(), distortion: vec![], modifier: vec![Box::new(Onion { thickness: 10.0 })], parent_id: None, sdf: Box::new(primitive::Circle { radius: 100.0 }) },
+        ];
+        let mut max_raw: f32 = 0.0;
+        let mut max_rescaled: f32 = 0.0;
+
+        for j in -40..40 {
+            for i in -40..40 {
+                let point = Vec2::new(i as f32 * 3.1 + 0.5, j as f32 * 2.9 + 0.5);
+                let distance = arena[1].get_distance(&arena, point);
+
+                // The rescaling is local, it's accurate close to the edge where borders & anti-aliasing happen
+                if distance.abs() < 2.0 {
+                    let raw_distance = |point: Vec2| arena[0].get_distance(&arena, wave().map(point));
+                    let raw = Vec2::new(
+                        raw_distance(point + Vec2::new(0.5, 0.0)) - raw_distance(point - Vec2::new(0.5, 0.0)),
+                        raw_distance(point + Vec2::new(0.0, 0.5)) - raw_distance(point - Vec2::new(0.0, 0.5)),
+                    ).length();
+
+                    max_raw = max_raw.max(raw);
+                    max_rescaled = max_rescaled.max(get_gradient(&arena[1], &arena, point, 0.5).length());
+                }
+            }
+        }
+
+        // The wave stretches the space up to ~1.8x
+        assert!(max_raw > 1.5, "{}", max_raw);
+        assert!(max_rescaled < 1.05, "{}", max_rescaled);
+
+        assert_eq!(arena[2].get_distance(&arena, Vec2::new(100.0, 0.0)), -5.0);
+        assert_eq!(arena[2].get_distance(&arena, Vec2::zero()), 95.0);
+    }
 }
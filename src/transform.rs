@@ -1,53 +1,156 @@
 use bevy_math::{Mat3, Vec2};
 
+/// Affine transform of an object relative to its parent:
+/// scale, skew & rotation around `pivot` (in object space), then translation.
 #[derive(Debug, Clone, Copy)]
 pub struct Transform {
     pub x: f32,
     pub y: f32,
+    /// Degrees, clockwise
     pub rotation: f32,
-    pub scale: f32,
+    pub scale: Vec2,
+    /// Degrees, `skew.x` slants the `y` axis towards `x` & `skew.y` the `x` axis towards `y`
+    pub skew: Vec2,
+    pub pivot: Vec2,
 }
 
 impl Transform {
-    pub fn apply(&mut self, transform: Transform) {
-        self.x += transform.x;
-        self.y += transform.y;
-        self.rotation += transform.rotation;
-        self.scale *= transform.scale;
-    }
-
     pub fn new() -> Transform {
         Transform {
             x: 0.0,
             y: 0.0,
             rotation: 0.0,
-            scale: 1.0,
+            scale: Vec2::new(1.0, 1.0),
+            skew: Vec2::zero(),
+            pivot: Vec2::zero(),
         }
     }
 
+    /// Object to parent space matrix. A child's matrix goes after (to the right of) its parent's.
     pub fn to_matrix(&self) -> Mat3 {
-        Mat3::from_scale_angle_translation(
-            Vec2::new(1.0, 1.0),
-            -self.rotation.to_radians(),
-            Vec2::new(self.x, self.y),
-        )
+        let (sin, cos) = (-self.rotation.to_radians()).sin_cos();
+        let (skew_x, skew_y) = (self.skew.x.to_radians().tan(), self.skew.y.to_radians().tan());
+
+        // Rotation * skew * scale
+        let a = Vec2::new(cos + sin * -skew_y, sin + cos * skew_y) * self.scale.x;
+        let b = Vec2::new(cos * skew_x - sin, sin * skew_x + cos) * self.scale.y;
+        let translation = Vec2::new(self.x, self.y) + self.pivot - a * self.pivot.x - b * self.pivot.y;
+
+        Mat3::from_cols_array(&[a.x, a.y, 0.0, b.x, b.y, 0.0, translation.x, translation.y, 1.0])
     }
 
+    /// Maps a parent space point to object space, same as `to_matrix().inverse()` without building the matrix
     pub fn map(&self, point: Vec2) -> Vec2 {
         // Translate
-        let translate = Vec2::new(self.x as f32, self.y as f32);
-        let point = point - translate;
+        let point = point - Vec2::new(self.x, self.y) - self.pivot;
 
         // Rotate
-        let radians = self.rotation.to_radians();
-        let sin = radians.sin();
-        let cos = radians.cos();
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
         let point = Vec2::new(
             cos * point.x - sin * point.y,
             sin * point.x + cos * point.y,
         );
 
-        // Get scaled distance
-        point / self.scale
+        // Unskew
+        let (skew_x, skew_y) = (self.skew.x.to_radians().tan(), self.skew.y.to_radians().tan());
+        let point = Vec2::new(point.x - skew_x * point.y, point.y - skew_y * point.x) / (1.0 - skew_x * skew_y);
+
+        // Unscale
+        point / self.scale + self.pivot
+    }
+
+    /// Factor from object space distances to parent space distances.
+    /// Non-uniform scale & skew stretch some directions more than others, the smallest
+    /// stretch is used so distances are never overestimated.
+    pub fn get_distance_scale(&self) -> f32 {
+        let (skew_x, skew_y) = (self.skew.x.to_radians().tan(), self.skew.y.to_radians().tan());
+
+        // Singular values of skew * scale (rotation doesn't change them)
+        let a = Vec2::new(1.0, skew_y) * self.scale.x;
+        let b = Vec2::new(skew_x, 1.0) * self.scale.y;
+        let trace = a.length_squared() + b.length_squared();
+        let determinant = (a.x * b.y - a.y * b.x).abs();
+        let max = ((trace + (trace * trace - 4.0 * determinant * determinant).max(0.0).sqrt()) / 2.0).sqrt();
+
+        if max > 0.0 { determinant / max } else { 0.0 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_point(point: Vec2, expected: Vec2) {
+        assert!((point - expected).length() < 1e-4, "{:?} != {:?}", point, expected);
+    }
+
+    #[test]
+    fn test_map() {
+        let transform = Transform { x: 10.0, y: 5.0, rotation: 90.0, ..Transform::new() };
+
+        // Clockwise rotation, the object `+x` axis points down
+        assert_point(transform.map(Vec2::new(10.0, 3.0)), Vec2::new(2.0, 0.0));
+        assert_point(transform.to_matrix().transform_point2(Vec2::new(2.0, 0.0)), Vec2::new(10.0, 3.0));
+
+        let transform = Transform { scale: Vec2::new(2.0, 0.5), ..Transform::new() };
+
+        assert_point(transform.map(Vec2::new(4.0, 4.0)), Vec2::new(2.0, 8.0));
+
+        let transform = Transform { skew: Vec2::new(45.0, 0.0), ..Transform::new() };
+
+        assert_point(transform.to_matrix().transform_point2(Vec2::new(0.0, 1.0)), Vec2::new(1.0, 1.0));
+        assert_point(transform.map(Vec2::new(1.0, 1.0)), Vec2::new(0.0, 1.0));
+    }
+
+    #[test]
+    fn test_pivot() {
+        // Rotating around the pivot keeps it in place
+        let transform = Transform { rotation: 37.0, scale: Vec2::new(3.0, 2.0), pivot: Vec2::new(4.0, -1.0), ..Transform::new() };
+
+        assert_point(transform.map(Vec2::new(4.0, -1.0)), Vec2::new(4.0, -1.0));
+        assert_point(transform.to_matrix().transform_point2(Vec2::new(4.0, -1.0)), Vec2::new(4.0, -1.0));
+
+        let transform = Transform { x: 1.0, y: 2.0, ..transform };
+
+        assert_point(transform.map(Vec2::new(5.0, 1.0)), Vec2::new(4.0, -1.0));
+    }
+
+    #[test]
+    fn test_map_matches_matrix() {
+        let transform = Transform {
+            x: -7.0,
+            y: 3.5,
+            rotation: 123.0,
+            scale: Vec2::new(1.5, -0.75),
+            skew: Vec2::new(20.0, -10.0),
+            pivot: Vec2::new(2.0, 1.0),
+        };
+        let inverse = transform.to_matrix().inverse();
+
+        for i in 0..20 {
+            let point = Vec2::new(i as f32 * 3.1 - 30.0, i as f32 * -1.7 + 12.0);
+
+            assert_point(transform.map(point), inverse.transform_point2(point));
+            assert_point(transform.map(transform.to_matrix().transform_point2(point)), point);
+        }
+    }
+
+    #[test]
+    fn test_composition() {
+        // Child 10 units to the right of a parent rotated 90 degrees & scaled 2x, ends up 20 units down
+        let parent = Transform { x: 5.0, rotation: 90.0, scale: Vec2::new(2.0, 2.0), ..Transform::new() };
+        let child = Transform { x: 10.0, ..Transform::new() };
+        let matrix = parent.to_matrix() * child.to_matrix();
+
+        assert_point(matrix.transform_point2(Vec2::zero()), Vec2::new(5.0, -20.0));
+        assert_point(child.map(parent.map(Vec2::new(5.0, -20.0))), Vec2::zero());
+    }
+
+    #[test]
+    fn test_distance_scale() {
+        assert_eq!(Transform::new().get_distance_scale(), 1.0);
+        assert!((Transform { scale: Vec2::new(3.0, 3.0), rotation: 45.0, ..Transform::new() }.get_distance_scale() - 3.0).abs() < 1e-5);
+        assert!((Transform { scale: Vec2::new(3.0, -0.5), ..Transform::new() }.get_distance_scale() - 0.5).abs() < 1e-5);
+        assert!(Transform { skew: Vec2::new(30.0, 0.0), ..Transform::new() }.get_distance_scale() < 1.0);
     }
 }
@@ -15,6 +15,16 @@ pub fn smoothstep(a: f32, b: f32, t: f32) -> f32 {
     }
 }
 
+pub fn median(a: f32, b: f32, c: f32) -> f32 {
+    a.min(b).max(a.max(b).min(c))
+}
+
+/// Scratch directory for tests, unique per name & process so parallel runs don't clobber each other
+#[cfg(test)]
+pub fn get_temp_directory(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("sdf_2d_{}_{}", name, std::process::id()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -58,4 +68,12 @@ mod tests {
         assert_eq!(smoothstep(-10.0, -20.0, -20.0), 1.0);
         assert_eq!(smoothstep(-10.0, -20.0, -25.0), 1.0);
     }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(median(1.0, 2.0, 3.0), 2.0);
+        assert_eq!(median(3.0, 1.0, 2.0), 2.0);
+        assert_eq!(median(2.0, 3.0, 1.0), 2.0);
+        assert_eq!(median(1.0, 1.0, 3.0), 1.0);
+    }
 }